@@ -1463,7 +1463,10 @@ fn apply_continuation<F: LurkField>(
         {
             // Although Emit has no effect within the computation, it has an externally-visible side effect of
             // manifesting an explicit Thunk in the expr register of the execution trace.
-            Continuation::Emit { continuation } => Control::MakeThunk(result, env, continuation),
+            Continuation::Emit { continuation } => {
+                store.emit(&result);
+                Control::MakeThunk(result, env, continuation)
+            }
             _ => unreachable!(),
         },
         ContTag::Call0 => match cont_witness
@@ -2481,6 +2484,49 @@ mod test {
         );
     }
 
+    #[test]
+    fn emit_sink() {
+        use crate::store::{EmitSink, ScalarPtr};
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Debug, Default)]
+        struct Collect(Mutex<Vec<(ScalarPtr<Fr>, Option<String>)>>);
+
+        impl EmitSink<Fr> for Collect {
+            fn emit(&self, value: ScalarPtr<Fr>, printed: Option<String>) {
+                self.0.lock().unwrap().push((value, printed));
+            }
+
+            fn wants_printed(&self) -> bool {
+                true
+            }
+        }
+
+        let sink = Arc::new(Collect::default());
+        let s = &mut Store::<Fr>::with_emit_sink(sink.clone());
+        let expr = s.read("(begin (emit 123) (emit (cons 1 2)))").unwrap();
+        let env = empty_sym_env(s);
+        let (_, _, emitted) = Evaluator::new(expr, env, s, 100000).eval().unwrap();
+        assert_eq!(2, emitted.len());
+
+        let emitted = [s.num(123), s.read("(1 . 2)").unwrap()]
+            .iter()
+            .map(|ptr| (s.hash_expr(ptr).unwrap(), Some(ptr.fmt_to_string(s))))
+            .collect::<Vec<_>>();
+        assert_eq!(emitted, *sink.0.lock().unwrap());
+    }
+
+    #[test]
+    fn emit_without_sink_does_not_hash() {
+        let s = &mut Store::<Fr>::default();
+        let expr = s.read("(emit (cons 1 (cons 2 nil)))").unwrap();
+        let env = empty_sym_env(s);
+        let sizes = (s.cache_sizes(), s.reverse_map_sizes());
+        let (_, _, emitted) = Evaluator::new(expr, env, s, 100000).eval().unwrap();
+        assert_eq!(1, emitted.len());
+        assert_eq!(sizes, (s.cache_sizes(), s.reverse_map_sizes()));
+    }
+
     #[test]
     fn evaluate_lambda() {
         let s = &mut Store::<Fr>::default();
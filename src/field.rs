@@ -321,6 +321,12 @@ impl<'de, F: LurkField> Deserialize<'de> for FWrap<F> {
     {
         use serde::de::Error;
         let bytes: Vec<u8> = Vec::deserialize(deserializer)?;
+        if bytes.len() != F::default().to_repr().as_ref().len() {
+            return Err(D::Error::invalid_length(
+                bytes.len(),
+                &"the length of a field representation",
+            ));
+        }
         let f = F::from_bytes(&bytes).ok_or_else(|| {
             D::Error::custom(format!("expected field element as bytes, got {:?}", &bytes))
         })?;
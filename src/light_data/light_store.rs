@@ -51,7 +51,7 @@ impl<F: LurkField> Encodable for LightStore<F> {
 impl<F: LurkField> LightStore<F> {
     /// Leaf pointers are those whose values aren't hashes of any piece of data
     /// that's expected to be in the LightStore
-    fn is_ptr_leaf(&self, ptr: ScalarPtr<F>) -> bool {
+    pub(crate) fn is_ptr_leaf(ptr: ScalarPtr<F>) -> bool {
         match ptr.tag() {
             ExprTag::Num => true,
             ExprTag::Char => true,
@@ -242,7 +242,7 @@ impl<F: LurkField> LightStore<F> {
             if store.get_expr(&ptr).is_some() {
                 continue;
             }
-            if self.is_ptr_leaf(ptr) {
+            if Self::is_ptr_leaf(ptr) {
                 self.intern_leaf(ptr, store)?;
             } else {
                 self.intern_non_leaf(ptr, store, &mut stack)?;
@@ -255,7 +255,7 @@ impl<F: LurkField> LightStore<F> {
     fn to_scalar_store(&self) -> anyhow::Result<ScalarStore<F>> {
         let mut store = ScalarStore::default();
         for ptr in self.scalar_map.keys() {
-            if self.is_ptr_leaf(*ptr) {
+            if Self::is_ptr_leaf(*ptr) {
                 return Err(anyhow!("Leaf pointer found in LightStore: {ptr}"));
             }
             self.intern_ptr_data(*ptr, &mut store)?;
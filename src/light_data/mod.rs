@@ -13,6 +13,7 @@
 
 use anyhow::Result;
 use std::fmt::Display;
+use std::io::Read;
 
 #[cfg(not(target_arch = "wasm32"))]
 use proptest::prelude::*;
@@ -183,6 +184,63 @@ impl LightData {
         }
     }
 
+    /// Deserializes a `LightData` from a reader, consuming exactly the bytes of its serialization. Unlike `de`, the
+    /// serialized bytes are never held in memory all at once.
+    ///
+    /// # Errors
+    ///
+    /// This function errors if reading fails or the bytes read don't correspond to a valid serialization of LightData
+    pub fn de_reader<R: Read>(r: &mut R) -> anyhow::Result<Self> {
+        let (tag, size) = Self::de_header_reader(r)?;
+
+        if Self::tag_is_atom(tag) {
+            // Read through `take` rather than preallocating, since `size` comes from untrusted input.
+            let mut data = Vec::new();
+            r.by_ref().take(size as u64).read_to_end(&mut data)?;
+            if data.len() != size {
+                return Err(anyhow!("Error parsing light data: unexpected end of input"));
+            }
+            Ok(LightData::Atom(data))
+        } else {
+            let mut xs = Vec::new();
+            for _ in 0..size {
+                xs.push(LightData::de_reader(r)?);
+            }
+            Ok(LightData::Cell(xs))
+        }
+    }
+
+    /// Reads the header of a `Cell` from `r`, returning its number of children. The children are left unread, so that
+    /// they can be decoded one at a time with `de_reader`.
+    pub fn de_cell_header_reader<R: Read>(r: &mut R) -> anyhow::Result<usize> {
+        let (tag, size) = Self::de_header_reader(r)?;
+        if Self::tag_is_atom(tag) {
+            return Err(anyhow!("Error parsing light data: expected a cell"));
+        }
+        Ok(size)
+    }
+
+    /// Reads a tag byte and the size it encodes, along with any size bytes that follow it.
+    fn de_header_reader<R: Read>(r: &mut R) -> anyhow::Result<(u8, usize)> {
+        let mut tag = [0u8; 1];
+        r.read_exact(&mut tag)?;
+        let tag = tag[0];
+        let size = tag & 0b11_1111;
+
+        let size = if Self::tag_is_small(tag) {
+            match size {
+                0 => 64,
+                _ => size as usize,
+            }
+        } else {
+            let mut bytes = vec![0u8; size as usize];
+            r.read_exact(&mut bytes)?;
+            LightData::read_size_bytes(&bytes)
+                .ok_or_else(|| anyhow!("Error parsing light data: size too large"))?
+        };
+        Ok((tag, size))
+    }
+
     #[inline]
     fn de_aux(i: &[u8]) -> IResult<&[u8], Self> {
         let (i, tag) = take(1u8)(i)?;
@@ -360,5 +418,15 @@ pub mod tests {
             eprintln!("ser {:?}", ser);
             assert_eq!(x, de)
         }
+
+        #[test]
+        fn prop_light_data_reader(x in any::<LightData>()) {
+            let ser = x.ser();
+            let mut reader = std::io::Cursor::new(&ser);
+            let de = LightData::de_reader(&mut reader).expect("read LightData");
+            assert_eq!(x, de);
+            assert_eq!(ser.len() as u64, reader.position());
+            assert!(LightData::de_reader(&mut &ser[..ser.len() - 1]).is_err());
+        }
     }
 }
@@ -15,8 +15,9 @@ use std::{
 use crate::field::LurkField;
 use crate::uint::UInt;
 
-/// Finite field element type for Lurk. Has different internal representations to optimize evaluation.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Finite field element type for Lurk. Has different internal representations to optimize evaluation. Equality and
+/// hashing are by value, so the representations of the same field element are interchangeable.
+#[derive(Debug, Clone)]
 pub enum Num<F: LurkField> {
     /// a scalar field element in full field representation
     Scalar(F),
@@ -26,6 +27,14 @@ pub enum Num<F: LurkField> {
 
 impl<F: LurkField> Copy for Num<F> {}
 
+impl<F: LurkField> PartialEq for Num<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_value(other)
+    }
+}
+
+impl<F: LurkField> Eq for Num<F> {}
+
 impl<F: LurkField> Display for Num<F> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -42,7 +51,6 @@ impl<F: LurkField> Display for Num<F> {
     }
 }
 
-#[allow(clippy::derived_hash_with_manual_eq)]
 impl<F: LurkField> Hash for Num<F> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         match self {
@@ -175,6 +183,16 @@ impl<F: LurkField> DivAssign for Num<F> {
 }
 
 impl<F: LurkField> Num<F> {
+    /// Returns the additive identity, in its canonical `U64` representation.
+    pub fn zero() -> Self {
+        Num::U64(0)
+    }
+
+    /// Returns the multiplicative identity, in its canonical `U64` representation.
+    pub fn one() -> Self {
+        Num::U64(1)
+    }
+
     /// Determines if `self` is zero.
     pub fn is_zero(&self) -> bool {
         match self {
@@ -183,6 +201,14 @@ impl<F: LurkField> Num<F> {
         }
     }
 
+    /// Determines if `self` is one.
+    pub fn is_one(&self) -> bool {
+        match self {
+            Num::Scalar(s) => *s == F::one(),
+            Num::U64(n) => n == &1,
+        }
+    }
+
     fn is_less_than(&self, other: &Num<F>) -> bool {
         assert!(self != other);
         match (self.is_negative(), other.is_negative()) {
@@ -201,6 +227,49 @@ impl<F: LurkField> Num<F> {
         }
     }
 
+    /// Compares `self` and `other` as unsigned integers, i.e. by their canonical representatives in `[0, p)`. Unlike
+    /// `PartialOrd`, which implements Lurk's signed comparison, this treats `p - 1` as the largest value rather than as
+    /// `-1`. `U64` and `Scalar` representations of the same element compare equal.
+    pub fn cmp_as_integer(&self, other: &Num<F>) -> Ordering {
+        match (self, other) {
+            (Num::U64(a), Num::U64(b)) => a.cmp(b),
+            (a, b) => {
+                let (a, b) = (a.into_scalar().to_repr(), b.into_scalar().to_repr());
+                // `to_repr` is little-endian, so compare from the most significant byte down.
+                a.as_ref().iter().rev().cmp(b.as_ref().iter().rev())
+            }
+        }
+    }
+
+    /// Returns true if `self` and `other` are the same field element, whatever their representations. This is what
+    /// `==` compares.
+    pub fn eq_value(&self, other: &Num<F>) -> bool {
+        match (self, other) {
+            (Num::U64(a), Num::U64(b)) => a == b,
+            (a, b) => a.into_scalar() == b.into_scalar(),
+        }
+    }
+
+    /// Field division: `self` times the inverse of `other`, or `None` if `other` is zero. This is not integer division:
+    /// `7 / 2` is the element which doubled gives 7, not 3. See `int_div_rem` for that. `/=` computes the same value,
+    /// but panics on zero.
+    pub fn field_div(&self, other: &Num<F>) -> Option<Num<F>> {
+        if other.is_zero() {
+            return None;
+        }
+        let mut quotient = *self;
+        quotient /= *other;
+        Some(quotient)
+    }
+
+    /// Integer quotient and remainder of `self` by `other`, both taken as their canonical representatives in `[0, p)`.
+    /// Returns `None` unless both fit in a u64 and `other` is not zero.
+    pub fn int_div_rem(&self, other: &Num<F>) -> Option<(Num<F>, Num<F>)> {
+        let a = self.into_scalar().to_u64()?;
+        let b = other.into_scalar().to_u64()?;
+        Some((Num::U64(a.checked_div(b)?), Num::U64(a % b)))
+    }
+
     /// Returns the most negative value representable by `Num<F>`.
     pub fn most_negative() -> Self {
         Num::Scalar(F::most_negative())
@@ -228,6 +297,15 @@ impl<F: LurkField> Num<F> {
         }
     }
 
+    /// Returns the canonical representation of `self`: `U64` whenever the value fits in a u64, and `Scalar`
+    /// otherwise. Numerically equal values have equal canonical representations.
+    pub fn canonical(self) -> Self {
+        match self {
+            Num::Scalar(s) => s.to_u64().map_or(self, Num::U64),
+            Num::U64(_) => self,
+        }
+    }
+
     /// Creates a new `Num<F>` from the given scalar `s`.
     pub fn from_scalar(s: F) -> Self {
         Num::Scalar(s)
@@ -304,6 +382,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_num_serde() {
+        let p_minus_1 = -Fr::one();
+        for n in [
+            Num::U64(0),
+            Num::U64(1),
+            Num::Scalar(Fr::one()),
+            Num::Scalar(p_minus_1),
+        ] {
+            let ipld = libipld::serde::to_ipld(n).unwrap();
+            let m: Num<Fr> = libipld::serde::from_ipld(ipld).unwrap();
+            assert_eq!(Num::Scalar(n.into_scalar()), m);
+        }
+
+        // p itself is not canonical.
+        let mut p = p_minus_1.to_bytes();
+        p[0] += 1;
+        let ipld = libipld::serde::to_ipld(p.clone()).unwrap();
+        assert!(libipld::serde::from_ipld::<Num<Fr>>(ipld).is_err());
+
+        let ipld = libipld::serde::to_ipld(p[1..].to_vec()).unwrap();
+        assert!(libipld::serde::from_ipld::<Num<Fr>>(ipld).is_err());
+    }
+
     #[test]
     fn test_add_assign() {
         // u64 - u64 - no overflow
@@ -439,6 +541,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_field_div_int_div_rem() {
+        let seven = Num::<Fr>::U64(7);
+        let two = Num::Scalar(Fr::from(2));
+        let zero = Num::U64(0);
+
+        let field_quotient = seven.field_div(&two).unwrap();
+        assert_eq!(
+            Num::Scalar(Fr::from(7) * Fr::from(2).invert().unwrap()),
+            field_quotient
+        );
+        assert_ne!(Num::U64(3), field_quotient);
+        let mut doubled = field_quotient;
+        doubled *= two;
+        assert_eq!(seven, doubled);
+        assert_eq!(None, seven.field_div(&zero));
+
+        assert_eq!(Some((Num::U64(3), Num::U64(1))), seven.int_div_rem(&two));
+        assert_eq!(None, seven.int_div_rem(&zero));
+        let p_minus_one = Num::Scalar(Fr::zero() - Fr::one());
+        assert_eq!(None, p_minus_one.int_div_rem(&two));
+        assert_eq!(None, seven.int_div_rem(&p_minus_one));
+
+        // When the divisor divides exactly, the two agree.
+        let six = Num::<Fr>::U64(6);
+        assert_eq!(six.field_div(&two), six.int_div_rem(&two).map(|(q, _)| q));
+    }
+
+    #[test]
+    fn test_zero_one() {
+        let zero = Num::<Fr>::zero();
+        let one = Num::<Fr>::one();
+        assert!(zero.is_zero() && !zero.is_one());
+        assert!(one.is_one() && !one.is_zero());
+        assert!(Num::<Fr>::Scalar(Fr::one()).is_one());
+        assert!(!Num::<Fr>::U64(2).is_one());
+
+        let mut sum = one;
+        sum += zero;
+        assert_eq!(one, sum);
+        let mut product = Num::<Fr>::U64(7);
+        product *= one;
+        assert_eq!(Num::U64(7), product);
+
+        let mut store = crate::store::Store::<Fr>::default();
+        assert_eq!(store.num(Num::one()), store.num(1u64));
+        assert_eq!(store.num(Num::zero()), store.num(0u64));
+        assert_eq!(store.num(Num::one()), store.num(Num::Scalar(Fr::one())));
+    }
+
     #[test]
     fn test_num_hash() {
         use std::collections::hash_map::DefaultHasher;
@@ -463,6 +615,39 @@ mod tests {
         assert_eq!(a_hash, b_hash);
     }
 
+    #[test]
+    fn test_eq_value() {
+        let a = Num::<Fr>::U64(123);
+        let b = Num::Scalar(Fr::from(123));
+        assert!(a.eq_value(&b));
+        assert_eq!(a, b);
+        assert_ne!(a, Num::Scalar(Fr::from(124)));
+
+        let p_minus_one = Num::Scalar(Fr::zero() - Fr::one());
+        assert!(p_minus_one.eq_value(&p_minus_one));
+        assert!(!p_minus_one.eq_value(&Num::U64(u64::MAX)));
+
+        let mut store = crate::store::Store::<Fr>::default();
+        assert_eq!(store.intern_num(a), store.intern_num(b));
+        assert_ne!(store.intern_num(a), store.intern_num(p_minus_one));
+    }
+
+    #[test]
+    fn test_cmp_as_integer() {
+        let zero = Num::<Fr>::U64(0);
+        let one = Num::Scalar(Fr::one());
+        let p_minus_one = Num::Scalar(Fr::zero() - Fr::one());
+
+        assert_eq!(Ordering::Less, zero.cmp_as_integer(&one));
+        assert_eq!(Ordering::Less, one.cmp_as_integer(&p_minus_one));
+        assert_eq!(Ordering::Greater, p_minus_one.cmp_as_integer(&zero));
+        assert_eq!(Ordering::Equal, one.cmp_as_integer(&Num::U64(1)));
+        assert_eq!(Ordering::Equal, p_minus_one.cmp_as_integer(&p_minus_one));
+
+        // The signed comparison used by the evaluator disagrees: p - 1 is -1.
+        assert!(p_minus_one < zero);
+    }
+
     #[test]
     fn test_negative_positive() {
         let mns = Fr::most_negative();
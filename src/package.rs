@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use once_cell::sync::Lazy;
+
 use crate::sym::Sym;
 
 #[derive(Clone, Debug)]
@@ -47,6 +49,106 @@ pub const LURK_EXTERNAL_SYMBOL_NAMES: &[&str] = &[
     ">=",
 ];
 
+/// The canonical full names of `LURK_EXTERNAL_SYMBOL_NAMES`, in the same order, for interning without case conversion.
+pub(crate) static LURK_EXTERNAL_SYMBOL_FULL_NAMES: Lazy<Vec<String>> = Lazy::new(|| {
+    LURK_EXTERNAL_SYMBOL_NAMES
+        .iter()
+        .map(|name| format!(".LURK.{}", name.to_uppercase()))
+        .collect()
+});
+
+/// The symbols in `LURK_EXTERNAL_SYMBOL_NAMES`, in the same order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WellKnown {
+    Atom,
+    Begin,
+    Car,
+    Cdr,
+    Char,
+    Comm,
+    Commit,
+    Cons,
+    CurrentEnv,
+    Emit,
+    Eval,
+    Eq,
+    Hide,
+    If,
+    Lambda,
+    Let,
+    Letrec,
+    Nil,
+    Num,
+    U64,
+    Open,
+    Quote,
+    Secret,
+    Strcons,
+    T,
+    Underscore,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    NumEqual,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+}
+
+impl WellKnown {
+    pub const ALL: [WellKnown; 36] = [
+        Self::Atom,
+        Self::Begin,
+        Self::Car,
+        Self::Cdr,
+        Self::Char,
+        Self::Comm,
+        Self::Commit,
+        Self::Cons,
+        Self::CurrentEnv,
+        Self::Emit,
+        Self::Eval,
+        Self::Eq,
+        Self::Hide,
+        Self::If,
+        Self::Lambda,
+        Self::Let,
+        Self::Letrec,
+        Self::Nil,
+        Self::Num,
+        Self::U64,
+        Self::Open,
+        Self::Quote,
+        Self::Secret,
+        Self::Strcons,
+        Self::T,
+        Self::Underscore,
+        Self::Add,
+        Self::Sub,
+        Self::Mul,
+        Self::Div,
+        Self::Mod,
+        Self::NumEqual,
+        Self::Less,
+        Self::Greater,
+        Self::LessEqual,
+        Self::GreaterEqual,
+    ];
+
+    /// The symbol's name, as listed in `LURK_EXTERNAL_SYMBOL_NAMES`.
+    pub fn name(self) -> &'static str {
+        LURK_EXTERNAL_SYMBOL_NAMES[self as usize]
+    }
+
+    /// The symbol's canonical full name, e.g. `.LURK.LAMBDA`.
+    pub fn full_name(self) -> &'static str {
+        &LURK_EXTERNAL_SYMBOL_FULL_NAMES[self as usize]
+    }
+}
+
 impl Package {
     pub fn new(name: Sym) -> Self {
         Self {
@@ -16,6 +16,12 @@ pub enum Error {
 }
 
 impl<F: LurkField> Store<F> {
+    /// Read one expression from `input`, in the default package.
+    ///
+    /// This inverts the writer for expressions which print losslessly: reading the printed form of a num, u64, char,
+    /// keyword, symbol, string or list, proper or dotted, of such expressions yields a pointer to an equal expression.
+    /// Strings and symbols must not contain characters the writer leaves unescaped, such as `"` or whitespace. Funs,
+    /// commitments, thunks and opaque data print in forms which do not read back as themselves.
     pub fn read(&mut self, input: &str) -> Result<Ptr<F>, Error> {
         let package = Default::default();
 
@@ -209,7 +215,7 @@ impl<F: LurkField> Store<F> {
                                 Ok(self.intern_num(tmp))
                             }
                             _ => {
-                                if let Some(sym) = read_sym(chars)? {
+                                if let Some(sym) = read_sym(chars, self.case_mode())? {
                                     Ok(self.intern_sym_in_package(sym, package))
                                 } else {
                                     Ok(self.intern_sym_in_package(Sym::new("-".into()), package))
@@ -450,7 +456,7 @@ impl<F: LurkField> Store<F> {
         chars: &mut PeekMoreIterator<T>,
         package: &Package,
     ) -> Result<Ptr<F>, Error> {
-        if let Some(sym) = read_sym(chars)? {
+        if let Some(sym) = read_sym(chars, self.case_mode())? {
             if sym.is_root() {
                 // The root symbol cannot (currently) be read. A naked dot is an error except in the context of a list tail.
                 Err(Error::Syntax("Misplaced dot".into()))
@@ -471,9 +477,8 @@ impl<F: LurkField> Store<F> {
             match c {
                 '\\' => {
                     chars.next();
-                    if let Some(&c) = chars.peek() {
-                        chars.next();
-                        Ok(c.into())
+                    if let Some(c) = chars.next() {
+                        Ok(read_char_literal(c, chars)?.into())
                     } else {
                         Err(Error::Syntax("Could not read character".into()))
                     }
@@ -486,12 +491,54 @@ impl<F: LurkField> Store<F> {
     }
 }
 
+// Read the rest of a character literal whose first character after `#\` is `first`: a name from `CHAR_NAMES`, a
+// `u{hex}` codepoint, or just `first` itself.
+fn read_char_literal<T: Iterator<Item = char>>(
+    first: char,
+    chars: &mut PeekMoreIterator<T>,
+) -> Result<char, Error> {
+    match chars.peek() {
+        Some('{') if first == 'u' => {
+            chars.next();
+            let mut hex = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(c) => hex.push(c),
+                    None => return Err(Error::Syntax("Unterminated character codepoint".into())),
+                }
+            }
+            u32::from_str_radix(&hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| Error::Syntax(format!("Invalid character codepoint: {hex}")))
+        }
+        Some(c) if first.is_alphabetic() && c.is_alphabetic() => {
+            let mut name = first.to_string();
+            while let Some(&c) = chars.peek() {
+                if !c.is_alphabetic() {
+                    break;
+                }
+                name.push(c);
+                chars.next();
+            }
+            CHAR_NAMES
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, c)| *c)
+                .ok_or_else(|| Error::Syntax(format!("Unknown character name: {name}")))
+        }
+        _ => Ok(first),
+    }
+}
+
 // Read a symbol's canonical full name by first reading the path,
 // then constructing the canonical full name from the resulting path.
 fn read_sym<T: Iterator<Item = char>>(
     chars: &mut PeekMoreIterator<T>,
+    case_mode: CaseMode,
 ) -> Result<Option<Sym>, Error> {
-    let (is_keyword, path) = read_symbol_path(chars)?;
+    let (is_keyword, path) = read_symbol_path_with_case(chars, case_mode)?;
 
     if path.is_empty() {
         Ok(None)
@@ -503,6 +550,13 @@ fn read_sym<T: Iterator<Item = char>>(
 
 pub fn read_symbol_path<T: Iterator<Item = char>>(
     chars: &mut PeekMoreIterator<T>,
+) -> Result<(bool, Vec<String>), Error> {
+    read_symbol_path_with_case(chars, CaseMode::Upper)
+}
+
+pub fn read_symbol_path_with_case<T: Iterator<Item = char>>(
+    chars: &mut PeekMoreIterator<T>,
+    case_mode: CaseMode,
 ) -> Result<(bool, Vec<String>), Error> {
     let mut path = Vec::new();
 
@@ -519,7 +573,7 @@ pub fn read_symbol_path<T: Iterator<Item = char>>(
         path.push("".into());
     };
 
-    while let Ok(name) = read_symbol_name(chars) {
+    while let Ok(name) = read_symbol_name(chars, case_mode) {
         path.push(name);
 
         if chars.peek() == Some(&SYM_MARKER) {
@@ -535,8 +589,9 @@ pub fn read_symbol_path<T: Iterator<Item = char>>(
 
 fn read_symbol_name<T: Iterator<Item = char>>(
     chars: &mut PeekMoreIterator<T>,
+    case_mode: CaseMode,
 ) -> Result<String, Error> {
-    read_unquoted_symbol_name(chars).or_else(|_| read_quoted_symbol_name(chars))
+    read_unquoted_symbol_name(chars, case_mode).or_else(|_| read_quoted_symbol_name(chars))
 }
 
 fn read_quoted_symbol_name<T: Iterator<Item = char>>(
@@ -567,14 +622,28 @@ fn read_quoted_symbol_name<T: Iterator<Item = char>>(
     }
 }
 
-pub(crate) fn convert_sym_case(raw_name: &mut str) {
-    // In the future, we could support optional alternate case conventions,
-    // so all case conversion should be performed here.
-    raw_name.make_ascii_uppercase();
+/// How the reader converts the case of unquoted symbol names.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaseMode {
+    /// Convert unquoted names to upper case, so `foo` and `FOO` read as the same symbol.
+    #[default]
+    Upper,
+    /// Keep unquoted names exactly as written.
+    Preserve,
+}
+
+pub(crate) fn convert_sym_case(raw_name: &mut str, case_mode: CaseMode) {
+    // All case conversion should be performed here. A leading keyword marker is never affected, since ASCII case
+    // conversion leaves `:` unchanged, so only the remainder of a keyword's name is converted.
+    match case_mode {
+        CaseMode::Upper => raw_name.make_ascii_uppercase(),
+        CaseMode::Preserve => (),
+    }
 }
 
 pub(crate) fn read_unquoted_symbol_name<T: Iterator<Item = char>>(
     chars: &mut PeekMoreIterator<T>,
+    case_mode: CaseMode,
 ) -> Result<String, Error> {
     let mut name = String::new();
     let mut is_initial = true;
@@ -590,7 +659,7 @@ pub(crate) fn read_unquoted_symbol_name<T: Iterator<Item = char>>(
                 }
                 is_initial = false;
             }
-            convert_sym_case(&mut name);
+            convert_sym_case(&mut name, case_mode);
             Ok(name)
         } else {
             Err(Error::Syntax("Could not read unquoted symbol".into()))
@@ -627,7 +696,7 @@ pub(crate) fn maybe_quote_symbol_name_string(symbol_name: &str) -> Result<String
         Ok(out)
     };
 
-    if let Ok(unquoted) = read_unquoted_symbol_name(&mut chars) {
+    if let Ok(unquoted) = read_unquoted_symbol_name(&mut chars, CaseMode::Upper) {
         if !contains_dot && unquoted == symbol_name {
             Ok(symbol_name.into())
         } else {
@@ -641,6 +710,14 @@ pub(crate) fn maybe_quote_symbol_name_string(symbol_name: &str) -> Result<String
 pub const KEYWORD_MARKER: char = ':';
 pub const SYM_SEPARATOR: &str = ".";
 pub const SYM_MARKER: char = '.';
+/// Names of characters read and written as `#\name` rather than literally.
+pub const CHAR_NAMES: [(&str, char); 5] = [
+    ("space", ' '),
+    ("newline", '\n'),
+    ("tab", '\t'),
+    ("return", '\r'),
+    ("nul", '\0'),
+];
 
 pub fn names_keyword(name: &str) -> (bool, &str) {
     let names_keyword = name.starts_with(KEYWORD_MARKER);
@@ -725,11 +802,31 @@ fn skip_line_comment<T: Iterator<Item = char>>(chars: &mut PeekMoreIterator<T>)
 
 #[cfg(test)]
 mod test {
+    use crate::store::Pointer;
+    use crate::tag::ExprTag;
     use crate::writer::Write;
     use blstrs::Scalar as Fr;
 
     use super::*;
 
+    #[test]
+    fn read_keyword_case() {
+        let mut store = Store::<Fr>::default();
+        let lower = store.read(":foo").unwrap();
+        let upper = store.read(":FOO").unwrap();
+        assert_eq!(ExprTag::Key, lower.tag());
+        assert_eq!(lower, upper);
+
+        let mut store = Store::<Fr>::default();
+        store.set_case_mode(CaseMode::Preserve);
+        let lower = store.read(":foo").unwrap();
+        let upper = store.read(":FOO").unwrap();
+        assert_eq!(ExprTag::Key, lower.tag());
+        assert_eq!(ExprTag::Key, upper.tag());
+        assert_ne!(lower, upper);
+        assert_ne!(store.hash_expr(&lower), store.hash_expr(&upper));
+    }
+
     #[test]
     fn read_sym() {
         let test = |input, expected: &str| {
@@ -1214,6 +1311,28 @@ mod test {
         assert_eq!(input, printed);
     }
 
+    #[test]
+    fn read_write_char_names() {
+        let s = &mut Store::<Fr>::default();
+
+        let test = |s: &mut Store<Fr>, c: char, expected: &str| {
+            let ptr = s.get_char(c);
+            assert_eq!(expected, ptr.fmt_to_string(s));
+            assert_eq!(ptr, s.read(expected).unwrap());
+        };
+
+        test(s, 'a', r#"#\a"#);
+        test(s, ' ', r#"#\space"#);
+        test(s, '\n', r#"#\newline"#);
+        test(s, '\t', r#"#\tab"#);
+        test(s, '\u{7}', r#"#\u{7}"#);
+        test(s, 'u', r#"#\u"#);
+
+        let list = s.read(r#"(#\a #\space)"#).unwrap();
+        assert_eq!(r#"(#\a #\space)"#, list.fmt_to_string(s));
+        assert!(s.read(r#"#\spaceship"#).is_err());
+    }
+
     #[test]
     fn read_with_comments() {
         let mut s = Store::<Fr>::default();
@@ -1232,6 +1351,27 @@ mod test {
         );
     }
 
+    #[test]
+    fn read_write_round_trip() {
+        let s = &mut Store::<Fr>::default();
+        let one = s.num(1);
+        let big = s.num(crate::num::Num::Scalar(Fr::from(0u64) - Fr::from(1u64)));
+        let u64 = s.uint64(7);
+        let c = s.get_char('x');
+        let key = s.key("key");
+        let sym = s.sym("sym");
+        let string = s.str("a string");
+        let nil = s.nil();
+        let dotted = s.cons(sym, one);
+        let inner = s.list(&[string, dotted, nil]);
+        let expr = s.list(&[one, big, u64, c, key, sym, inner, inner]);
+
+        let printed = expr.fmt_to_string(s);
+        let read = s.read(&printed).unwrap();
+        assert_eq!(s.hash_expr(&expr), s.hash_expr(&read));
+        assert_eq!(printed, read.fmt_to_string(s));
+    }
+
     #[test]
     fn read_non_fractions() {
         let mut s = Store::<Fr>::default();
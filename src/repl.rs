@@ -1,10 +1,8 @@
 use crate::error::LurkError;
 use crate::eval::{empty_sym_env, Evaluator, IO};
 use crate::field::LurkField;
-use crate::light_data::{Encodable, LightData, LightStore};
 use crate::package::Package;
 use crate::parser;
-use crate::scalar_store::ScalarStore;
 use crate::store::{ContPtr, Expression, Pointer, Ptr, Store};
 use crate::tag::ContTag;
 use crate::writer::Write;
@@ -197,11 +195,8 @@ fn repl_aux<P: AsRef<Path>, F: LurkField, T: ReplTrait<F>>(
 ) -> Result<()> {
     let received_light_store = light_store.is_some();
     let mut s = light_store
-        .and_then(|light_store_path| fs::read(light_store_path).ok())
-        .and_then(|bytes| LightData::de(&bytes).ok())
-        .and_then(|ld| Encodable::de(&ld).ok())
-        .and_then(|store: LightStore<F>| ScalarStore::try_from(store).ok())
-        .and_then(|mut scalar_store: ScalarStore<F>| scalar_store.to_store())
+        .and_then(|light_store_path| fs::File::open(light_store_path).ok())
+        .and_then(|file| Store::from_reader(io::BufReader::new(file)).ok())
         .tap_none(|| {
             if received_light_store {
                 eprintln!("Failed to load light store. Starting with empty store.")
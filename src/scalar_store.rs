@@ -1,10 +1,10 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::field::LurkField;
 
 #[cfg(not(target_arch = "wasm32"))]
 use crate::field::FWrap;
-use crate::store::{Pointer, Ptr, ScalarContPtr, ScalarPtr, Store};
+use crate::store::{field_hash, HashConstants, Pointer, Ptr, ScalarContPtr, ScalarPtr, Store};
 use crate::tag::{ExprTag, Op1, Op2};
 use crate::{Num, Sym, UInt};
 #[cfg(not(target_arch = "wasm32"))]
@@ -124,6 +124,81 @@ impl<F: LurkField> ScalarStore<F> {
         (*x).as_ref()
     }
 
+    /// Whether `a` in this store and `b` in `other` denote the same structure. Since `ScalarPtr`s are content hashes,
+    /// this requires `a == b`, and additionally checks that both stores hold identical preimages for every reachable
+    /// `ScalarPtr`, so that neither store is missing data or inconsistent with the other. Opaque entries match only
+    /// opaque entries.
+    pub fn structurally_equal(
+        &self,
+        a: &ScalarPtr<F>,
+        other: &ScalarStore<F>,
+        b: &ScalarPtr<F>,
+    ) -> bool {
+        let mut pending = vec![(*a, *b)];
+        let mut visited = BTreeSet::new();
+
+        while let Some((a, b)) = pending.pop() {
+            if a != b {
+                return false;
+            }
+            if !visited.insert(a) {
+                continue;
+            }
+            match (self.scalar_map.get(&a), other.scalar_map.get(&b)) {
+                (Some(Some(x)), Some(Some(y))) => {
+                    if x != y {
+                        return false;
+                    }
+                    if let Some(children) = Self::child_scalar_ptrs(x) {
+                        pending.extend(children.into_iter().map(|child| (child, child)));
+                    }
+                }
+                (Some(None), Some(None)) => (),
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// All the `ScalarPtr`s reachable from `root` in this store, in post-order: every `ScalarPtr` appears after all of
+    /// its children, and each appears once. Opaque or missing entries are leaves.
+    fn reachable_scalar_ptrs_topo(&self, root: &ScalarPtr<F>) -> Vec<ScalarPtr<F>> {
+        let mut order = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut pending = vec![(*root, false)];
+
+        while let Some((scalar_ptr, expanded)) = pending.pop() {
+            if expanded {
+                order.push(scalar_ptr);
+                continue;
+            }
+            if !visited.insert(scalar_ptr) {
+                continue;
+            }
+            pending.push((scalar_ptr, true));
+            if let Some(children) = self.get_expr(&scalar_ptr).and_then(Self::child_scalar_ptrs) {
+                pending.extend(children.into_iter().rev().map(|child| (child, false)));
+            }
+        }
+        order
+    }
+
+    /// A single commitment to the subgraph reachable from `root`, obtained by folding the post-ordered reachable
+    /// `ScalarPtr`s into an accumulator with Poseidon. Equal subgraphs commit equally, regardless of whatever else the
+    /// store holds.
+    pub fn root_commitment(&self, root: &ScalarPtr<F>) -> F {
+        let constants = HashConstants::<F>::constants_cache();
+
+        self.reachable_scalar_ptrs_topo(root)
+            .into_iter()
+            .fold(F::zero(), |acc, scalar_ptr| {
+                field_hash(
+                    &[acc, scalar_ptr.tag_field(), *scalar_ptr.value(), F::zero()],
+                    || constants.c4(),
+                )
+            })
+    }
+
     pub fn to_store_with_expr(&mut self, ptr: &ScalarPtr<F>) -> Option<(Store<F>, Ptr<F>)> {
         if self.pending_scalar_ptrs.is_empty() {
             let mut store = Store::new();
@@ -136,6 +211,7 @@ impl<F: LurkField> ScalarStore<F> {
             for ptr in self.scalar_cont_map.keys() {
                 store.intern_scalar_cont_ptr(*ptr, self);
             }
+            store.validate().ok()?;
             Some((store, ptr))
         } else {
             None
@@ -151,6 +227,7 @@ impl<F: LurkField> ScalarStore<F> {
             for ptr in self.scalar_cont_map.keys() {
                 store.intern_scalar_cont_ptr(*ptr, self);
             }
+            store.validate().ok()?;
             Some(store)
         } else {
             None
@@ -439,6 +516,61 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_structurally_equal() {
+        let src = "(lambda (x) (cons x (quote (1 . \"two\"))))";
+        let build = |src| {
+            let mut store = Store::<Fr>::default();
+            let expr = store.read(src).unwrap();
+            store.hydrate_scalar_cache();
+            let (scalar_store, scalar_expr) = ScalarStore::new_with_expr(&store, &expr);
+            (scalar_store, scalar_expr.unwrap())
+        };
+
+        let (scalar_store1, expr1) = build(src);
+        let (scalar_store2, expr2) = build(src);
+        assert!(scalar_store1.structurally_equal(&expr1, &scalar_store2, &expr2));
+        assert!(scalar_store2.structurally_equal(&expr2, &scalar_store1, &expr1));
+
+        let (scalar_store3, expr3) = build("(lambda (x) (cons x (quote (1 . \"three\"))))");
+        assert!(!scalar_store1.structurally_equal(&expr1, &scalar_store3, &expr3));
+
+        // A store missing the preimage of a reachable child is not consistent.
+        let mut incomplete = scalar_store2.clone();
+        let (car, _) = match incomplete.get_expr(&expr2) {
+            Some(ScalarExpression::Cons(car, cdr)) => (*car, *cdr),
+            _ => panic!("expected a cons"),
+        };
+        incomplete.scalar_map.remove(&car);
+        assert!(!scalar_store1.structurally_equal(&expr1, &incomplete, &expr2));
+    }
+
+    #[test]
+    fn test_root_commitment() {
+        let build = |src| {
+            let mut store = Store::<Fr>::default();
+            let expr = store.read(src).unwrap();
+            store.hydrate_scalar_cache();
+            let (scalar_store, scalar_expr) = ScalarStore::new_with_expr(&store, &expr);
+            (store, scalar_store, scalar_expr.unwrap())
+        };
+
+        let (_, scalar_store1, expr1) = build("(1 (2 . \"three\") 4)");
+        let (mut store2, mut scalar_store2, expr2) = build("(1 (2 . \"three\") 4)");
+        let commitment = scalar_store1.root_commitment(&expr1);
+        assert_eq!(commitment, scalar_store2.root_commitment(&expr2));
+
+        // Unrelated data in the store does not affect the commitment.
+        let unrelated = store2.read("(lambda (x) (+ x 5))").unwrap();
+        store2.hydrate_scalar_cache();
+        scalar_store2.add_one_ptr(&store2, &unrelated).unwrap();
+        assert_eq!(commitment, scalar_store2.root_commitment(&expr2));
+
+        // Changing a leaf changes the commitment.
+        let (_, scalar_store3, expr3) = build("(1 (2 . \"three\") 5)");
+        assert_ne!(commitment, scalar_store3.root_commitment(&expr3));
+    }
+
     #[test]
     fn test_expr_ipld() {
         let test = |src| {
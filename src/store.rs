@@ -1,12 +1,16 @@
 use anyhow::anyhow;
 use generic_array::typenum::{U3, U4, U6, U8};
+#[cfg(not(feature = "sha"))]
 use neptune::Poseidon;
 #[cfg(not(target_arch = "wasm32"))]
 use proptest_derive::Arbitrary;
 use rayon::prelude::*;
-use std::collections::VecDeque;
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::{fmt, marker::PhantomData};
 use string_interner::symbol::{Symbol, SymbolUsize};
@@ -22,10 +26,14 @@ use libipld::Multihash;
 
 use crate::light_data::Encodable;
 use crate::light_data::LightData;
+use crate::light_data::LightExpr;
+use crate::light_data::LightStore;
 
 use crate::field::{FWrap, LurkField};
-use crate::package::{Package, LURK_EXTERNAL_SYMBOL_NAMES};
-use crate::parser::{convert_sym_case, names_keyword};
+use crate::package::{
+    Package, WellKnown, LURK_EXTERNAL_SYMBOL_FULL_NAMES, LURK_EXTERNAL_SYMBOL_NAMES,
+};
+use crate::parser::{convert_sym_case, names_keyword, CaseMode, SYM_MARKER, SYM_SEPARATOR};
 use crate::scalar_store::{ScalarContinuation, ScalarExpression, ScalarStore};
 use crate::sym::Sym;
 use crate::tag::{ContTag, ExprTag, Op1, Op2, Tag};
@@ -68,6 +76,8 @@ pub(crate) struct HashConstants<F: LurkField> {
     c4: OnceCell<PoseidonConstants<F, U4>>,
     c6: OnceCell<PoseidonConstants<F, U6>>,
     c8: OnceCell<PoseidonConstants<F, U8>>,
+    /// Number of `PoseidonConstants` built so far, across all arities.
+    builds: AtomicUsize,
 }
 
 impl<F: LurkField> Default for HashConstants<F> {
@@ -77,37 +87,81 @@ impl<F: LurkField> Default for HashConstants<F> {
             c4: OnceCell::new(),
             c6: OnceCell::new(),
             c8: OnceCell::new(),
+            builds: AtomicUsize::new(0),
         }
     }
 }
 
+/// Process-wide `HashConstants`, keyed by the `TypeId` of their field, so stores over the same field share them.
+static CONSTANTS_CACHE: OnceCell<dashmap::DashMap<TypeId, Arc<dyn Any + Send + Sync>>> =
+    OnceCell::new();
+
 impl<F: LurkField> HashConstants<F> {
+    /// Returns the `HashConstants` shared by every store over `F`, creating them on first use.
+    pub(crate) fn constants_cache() -> Arc<Self> {
+        CONSTANTS_CACHE
+            .get_or_init(Default::default)
+            .entry(TypeId::of::<F>())
+            .or_insert_with(|| Arc::new(Self::default()) as Arc<dyn Any + Send + Sync>)
+            .clone()
+            .downcast::<Self>()
+            .expect("constants cached under the wrong field")
+    }
+
+    fn build<T>(&self, f: impl FnOnce() -> T) -> T {
+        self.builds.fetch_add(1, Ordering::Relaxed);
+        f()
+    }
+
+    /// Number of `PoseidonConstants` built by these `HashConstants`.
+    pub(crate) fn build_count(&self) -> usize {
+        self.builds.load(Ordering::Relaxed)
+    }
+
     pub fn c3(&self) -> &PoseidonConstants<F, U3> {
-        self.c3.get_or_init(|| PoseidonConstants::new())
+        self.c3.get_or_init(|| self.build(PoseidonConstants::new))
     }
 
     pub fn c4(&self) -> &PoseidonConstants<F, U4> {
-        self.c4.get_or_init(|| PoseidonConstants::new())
+        self.c4.get_or_init(|| self.build(PoseidonConstants::new))
     }
 
     pub fn c6(&self) -> &PoseidonConstants<F, U6> {
-        self.c6.get_or_init(|| PoseidonConstants::new())
+        self.c6.get_or_init(|| self.build(PoseidonConstants::new))
     }
 
     pub fn c8(&self) -> &PoseidonConstants<F, U8> {
-        self.c8.get_or_init(|| PoseidonConstants::new())
+        self.c8.get_or_init(|| self.build(PoseidonConstants::new))
     }
 
     pub fn constants(&self, arity: HashArity) -> HashConst<F> {
         match arity {
-            HashArity::A3 => HashConst::A3(self.c3.get_or_init(|| PoseidonConstants::new())),
-            HashArity::A4 => HashConst::A4(self.c4.get_or_init(|| PoseidonConstants::new())),
-            HashArity::A6 => HashConst::A6(self.c6.get_or_init(|| PoseidonConstants::new())),
-            HashArity::A8 => HashConst::A8(self.c8.get_or_init(|| PoseidonConstants::new())),
+            HashArity::A3 => HashConst::A3(self.c3()),
+            HashArity::A4 => HashConst::A4(self.c4()),
+            HashArity::A6 => HashConst::A6(self.c6()),
+            HashArity::A8 => HashConst::A8(self.c8()),
         }
     }
 }
 
+/// How a store hashes pairs of `ScalarPtr`s: conses, strings and symbol path segments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PairHash {
+    /// `[a_tag, a_value, b_tag, b_value]` with the arity-4 constants. This is what the circuit expects.
+    #[default]
+    Arity4,
+    /// `[a_tag + b_tag * 2^16, a_value, b_value]` with the arity-3 constants: tags fit in 16 bits, so packing them
+    /// into one element allows a narrower permutation.
+    Arity3,
+}
+
+/// One step of a path through nested conses, as taken by `Store::nth_car`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CarCdr {
+    Car,
+    Cdr,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum HashScalar {
     Create,
@@ -116,6 +170,77 @@ pub enum HashScalar {
 
 type IndexSet<K> = indexmap::IndexSet<K, ahash::RandomState>;
 
+/// Storage for the entries of a substore, addressed by insertion index. A `Set` deduplicates its entries, so equal
+/// entries share an index. An `Arena` is append-only and never hashes its entries, so every insertion gets a new
+/// index: faster for data known to be unique, but equal entries are no longer pointer-equal.
+#[derive(Debug)]
+pub(crate) enum Substore<T> {
+    Set(IndexSet<T>),
+    Arena(Vec<T>),
+}
+
+impl<T> Default for Substore<T> {
+    fn default() -> Self {
+        Substore::Set(Default::default())
+    }
+}
+
+impl<T: Hash + Eq + Clone> Substore<T> {
+    /// A copy without spare capacity.
+    fn shrunk_clone(&self) -> Self {
+        match self {
+            Substore::Set(set) => Substore::Set(shrunk_clone(set)),
+            Substore::Arena(arena) => Substore::Arena(arena.clone()),
+        }
+    }
+}
+
+/// A copy of `set` without spare capacity.
+fn shrunk_clone<T: Hash + Eq + Clone>(set: &IndexSet<T>) -> IndexSet<T> {
+    let mut set = set.clone();
+    set.shrink_to_fit();
+    set
+}
+
+impl<T: Hash + Eq> Substore<T> {
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Substore::Set(set) => set.len(),
+            Substore::Arena(arena) => arena.len(),
+        }
+    }
+
+    pub(crate) fn get_index(&self, index: usize) -> Option<&T> {
+        match self {
+            Substore::Set(set) => set.get_index(index),
+            Substore::Arena(arena) => arena.get(index),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> {
+        (0..self.len()).map(move |index| self.get_index(index).unwrap())
+    }
+
+    /// Whether inserting `value` would add a new entry.
+    fn would_insert(&self, value: &T) -> bool {
+        match self {
+            Substore::Set(set) => !set.contains(value),
+            Substore::Arena(_) => true,
+        }
+    }
+
+    /// Insert `value`, returning its index and whether it was newly added, as `IndexSet::insert_full`.
+    pub(crate) fn insert_full(&mut self, value: T) -> (usize, bool) {
+        match self {
+            Substore::Set(set) => set.insert_full(value),
+            Substore::Arena(arena) => {
+                arena.push(value);
+                (arena.len() - 1, true)
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 struct StringSet(
     string_interner::StringInterner<
@@ -130,9 +255,39 @@ impl Default for StringSet {
     }
 }
 
+impl StringSet {
+    /// A copy holding the same strings at the same indices: the backend's indices are buffer offsets, which
+    /// re-interning in order reproduces.
+    fn shrunk_clone(&self) -> Self {
+        let mut set = StringSet::default();
+        for (_, s) in &self.0 {
+            set.0.get_or_intern(s);
+        }
+        set.0.shrink_to_fit();
+        set
+    }
+
+    /// Approximate length of the interner's buffer. The backend does not expose it, but stores each string as its
+    /// LEB128-encoded length followed by its bytes, so this can be recomputed from the interned strings.
+    fn buffer_bytes(&self) -> usize {
+        self.0
+            .into_iter()
+            .map(|(_, s)| {
+                let mut prefix = 1;
+                let mut len = s.len();
+                while len >= 0x80 {
+                    len >>= 7;
+                    prefix += 1;
+                }
+                prefix + s.len()
+            })
+            .sum()
+    }
+}
+
 #[derive(Debug)]
 pub struct Store<F: LurkField> {
-    pub(crate) cons_store: IndexSet<(Ptr<F>, Ptr<F>)>,
+    pub(crate) cons_store: Substore<(Ptr<F>, Ptr<F>)>,
     pub(crate) comm_store: IndexSet<(FWrap<F>, Ptr<F>)>,
 
     fun_store: IndexSet<(Ptr<F>, Ptr<F>, Ptr<F>)>,
@@ -170,20 +325,300 @@ pub struct Store<F: LurkField> {
     dehydrated_cont: Vec<ContPtr<F>>,
     opaque_raw_ptr_count: usize,
 
+    /// Forward cache of `ScalarPtr`s, consulted first by `hash_expr_aux`, so that hashing a structure which shares an
+    /// already-hashed subtree does not walk that subtree again.
     pointer_scalar_ptr_cache: dashmap::DashMap<Ptr<F>, ScalarPtr<F>>,
 
     pub(crate) lurk_package: Arc<Package>,
     constants: OnceCell<NamedConstants<F>>,
+
+    /// Maximum number of primary entries the `try_intern_*` methods may grow the store to.
+    max_entries: Option<usize>,
+    /// How `read` converts the case of unquoted symbol names.
+    case_mode: CaseMode,
+    /// Whether the writer prints `(quote x)` as `'x`.
+    abbreviate_quote: bool,
+    /// Indices in `sym_store` of the well-known symbols, in `WellKnown` order. `Default` interns them first, so these
+    /// are increasing, but as the interner's indices are buffer offsets they are not contiguous.
+    wellknown_syms: Vec<usize>,
+    /// Indices in `sym_store` of the symbols `with_wellknown_syms` interned, in order. `try_merge` checks that two
+    /// stores agree on these.
+    vocabulary: Vec<usize>,
+    /// Full names of the `vocabulary` symbols as `with_wellknown_syms` interned them, which `reset` interns again.
+    /// Unaffected by `rename_sym`.
+    vocabulary_names: Vec<String>,
+    /// Names of symbols renamed by `rename_sym`, by index in `sym_store`. These take precedence over the interner.
+    sym_renames: HashMap<usize, String>,
+    /// The inverse of `sym_renames`: the index in `sym_store` of each name it assigns.
+    sym_rename_index: HashMap<String, usize>,
+    /// Whether interning the name `NIL` yields an ordinary symbol rather than nil. See `with_nil_as_symbol`.
+    nil_as_symbol: bool,
+    /// How pairs of `ScalarPtr`s are hashed. See `with_pair_hash`.
+    pair_hash: PairHash,
+    /// Where emitted values go. See `with_emit_sink`.
+    emit_sink: Arc<dyn EmitSink<F>>,
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 struct PoseidonCache<F: LurkField> {
     a3: dashmap::DashMap<CacheKey<F, 3>, F, ahash::RandomState>,
     a4: dashmap::DashMap<CacheKey<F, 4>, F, ahash::RandomState>,
     a6: dashmap::DashMap<CacheKey<F, 6>, F, ahash::RandomState>,
     a8: dashmap::DashMap<CacheKey<F, 8>, F, ahash::RandomState>,
 
-    constants: HashConstants<F>,
+    constants: Arc<HashConstants<F>>,
+    hasher: Arc<dyn LurkHasher<F>>,
+}
+
+/// The fixed-arity field hashes a store computes `ScalarPtr`s and `ScalarContPtr`s with. Every other hash is built from
+/// these, except that `hash_string` can take over the hashes of strings, and with them those of symbols.
+///
+/// WARNING: a store using anything but `PoseidonHasher` has different `ScalarPtr`s, which neither the circuit nor
+/// `ScalarStore` can reproduce. This is meant for experimentation.
+pub trait LurkHasher<F: LurkField>: fmt::Debug + Send + Sync {
+    fn hash3(&self, preimage: &[F; 3]) -> F;
+    fn hash4(&self, preimage: &[F; 4]) -> F;
+    fn hash6(&self, preimage: &[F; 6]) -> F;
+    fn hash8(&self, preimage: &[F; 8]) -> F;
+
+    /// The hash of the non-empty string `s`, or `None` to hash it as a cons of its first character and the rest of the
+    /// string, which is the default. Every suffix of an interned string is hashed through this, and symbols are hashed
+    /// from the hashes of their path segments. Results are not cached.
+    fn hash_string(&self, _s: &str) -> Option<F> {
+        None
+    }
+}
+
+/// The default `LurkHasher`: Poseidon, or SHA-256 with the `sha` feature. See `field_hash`.
+#[derive(Clone, Debug)]
+pub struct PoseidonHasher<F: LurkField> {
+    constants: Arc<HashConstants<F>>,
+}
+
+impl<F: LurkField> Default for PoseidonHasher<F> {
+    fn default() -> Self {
+        Self {
+            constants: HashConstants::constants_cache(),
+        }
+    }
+}
+
+impl<F: LurkField> LurkHasher<F> for PoseidonHasher<F> {
+    fn hash3(&self, preimage: &[F; 3]) -> F {
+        field_hash(preimage, || self.constants.c3())
+    }
+
+    fn hash4(&self, preimage: &[F; 4]) -> F {
+        field_hash(preimage, || self.constants.c4())
+    }
+
+    fn hash6(&self, preimage: &[F; 6]) -> F {
+        field_hash(preimage, || self.constants.c6())
+    }
+
+    fn hash8(&self, preimage: &[F; 8]) -> F {
+        field_hash(preimage, || self.constants.c8())
+    }
+}
+
+/// One call to a `LurkHasher`, as recorded by `Store::hash_expr_traced`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashStep<F: LurkField> {
+    pub arity: usize,
+    pub preimage: Vec<F>,
+}
+
+/// A `LurkHasher` recording every call before passing it on to `inner`.
+#[derive(Debug)]
+struct TracingHasher<F: LurkField> {
+    inner: Arc<dyn LurkHasher<F>>,
+    steps: std::sync::Mutex<Vec<HashStep<F>>>,
+}
+
+impl<F: LurkField> TracingHasher<F> {
+    fn record(&self, preimage: &[F]) {
+        self.steps.lock().unwrap().push(HashStep {
+            arity: preimage.len(),
+            preimage: preimage.to_vec(),
+        });
+    }
+}
+
+impl<F: LurkField> LurkHasher<F> for TracingHasher<F> {
+    fn hash3(&self, preimage: &[F; 3]) -> F {
+        self.record(preimage);
+        self.inner.hash3(preimage)
+    }
+
+    fn hash4(&self, preimage: &[F; 4]) -> F {
+        self.record(preimage);
+        self.inner.hash4(preimage)
+    }
+
+    fn hash6(&self, preimage: &[F; 6]) -> F {
+        self.record(preimage);
+        self.inner.hash6(preimage)
+    }
+
+    fn hash8(&self, preimage: &[F; 8]) -> F {
+        self.record(preimage);
+        self.inner.hash8(preimage)
+    }
+
+    /// Not recorded, having no field preimage.
+    fn hash_string(&self, s: &str) -> Option<F> {
+        self.inner.hash_string(s)
+    }
+}
+
+/// Where the `get_hash_components_*` helpers take the hashes of a preimage's children from: the store itself, or the
+/// separate pass of `Store::hash_expr_traced`.
+trait ChildHashes<F: LurkField> {
+    fn expr_hash(&self, ptr: &Ptr<F>) -> Option<ScalarPtr<F>>;
+    fn cont_hash(&self, ptr: &ContPtr<F>) -> Option<ScalarContPtr<F>>;
+}
+
+impl<F: LurkField> ChildHashes<F> for Store<F> {
+    fn expr_hash(&self, ptr: &Ptr<F>) -> Option<ScalarPtr<F>> {
+        self.get_expr_hash(ptr)
+    }
+
+    fn cont_hash(&self, ptr: &ContPtr<F>) -> Option<ScalarContPtr<F>> {
+        self.hash_cont(ptr)
+    }
+}
+
+/// The hashing pass of `Store::hash_expr_traced`. Every hash goes through `cache`, whose hasher records it, and the
+/// `ScalarPtr`s computed are memoized here rather than in `store`, which is only read.
+struct TracedHashing<'a, F: LurkField> {
+    store: &'a Store<F>,
+    cache: PoseidonCache<F>,
+    exprs: dashmap::DashMap<Ptr<F>, ScalarPtr<F>>,
+    conts: dashmap::DashMap<ContPtr<F>, ScalarContPtr<F>>,
+}
+
+impl<F: LurkField> TracedHashing<'_, F> {
+    fn pair_hash(&self, a: ScalarPtr<F>, b: ScalarPtr<F>) -> F {
+        self.store.hash_scalar_ptrs_2_in(&self.cache, &[a, b])
+    }
+
+    /// As `Store::hash_string`: each suffix is hashed with the character before it, unless the hasher takes over.
+    fn string_hash(&self, s: &str) -> F {
+        s.char_indices().rev().fold(F::zero(), |acc, (i, c)| {
+            self.cache.hash_string(&s[i..]).unwrap_or_else(|| {
+                let c = ScalarPtr::from_parts(ExprTag::Char, F::from(u32::from(c) as u64));
+                self.pair_hash(c, ScalarPtr::from_parts(ExprTag::Str, acc))
+            })
+        })
+    }
+
+    /// As `Store::hash_symbol`: each path segment below the root is hashed with its parent symbol.
+    fn symbol_hash(&self, sym: &Sym) -> F {
+        if sym.is_root() {
+            return F::zero();
+        }
+        sym.path().iter().skip(1).fold(F::zero(), |acc, name| {
+            let name = ScalarPtr::from_parts(ExprTag::Str, self.string_hash(name));
+            self.pair_hash(name, ScalarPtr::from_parts(ExprTag::Sym, acc))
+        })
+    }
+
+    fn hash(&self, ptr: &Ptr<F>) -> Option<ScalarPtr<F>> {
+        use ExprTag::*;
+
+        let store = self.store;
+        if ptr.is_opaque() {
+            return store.opaque_map.get(ptr).map(|s| *s);
+        }
+        let hash = match ptr.tag() {
+            Nil | Sym | Key => self.symbol_hash(&store.fetch_sym(ptr)?),
+            Str => self.string_hash(store.fetch_str(ptr)?),
+            Cons => {
+                let (car, cdr) = store.fetch_cons(ptr)?;
+                self.pair_hash(self.expr_hash(car)?, self.expr_hash(cdr)?)
+            }
+            Fun => {
+                let (arg, body, closed_env) = store.fetch_fun(ptr)?;
+                let children = [
+                    self.expr_hash(arg)?,
+                    self.expr_hash(body)?,
+                    self.expr_hash(closed_env)?,
+                ];
+                store.hash_scalar_ptrs_3_in(&self.cache, &children)
+            }
+            Comm => {
+                let (secret, payload) = store.fetch_comm(ptr)?;
+                commitment_hash_in(&self.cache, secret.0, self.expr_hash(payload)?)
+            }
+            Thunk => {
+                let components = store.thunk_hash_components(self, store.fetch_thunk(ptr)?)?;
+                self.cache.hash4(&components)
+            }
+            Num => return store.hash_num(*ptr, HashScalar::Get),
+            Char => return store.hash_char(*ptr, HashScalar::Get),
+            U64 => return store.hash_uint(*ptr, HashScalar::Get),
+        };
+        Some(ScalarPtr::from_parts(ptr.tag(), hash))
+    }
+}
+
+impl<F: LurkField> ChildHashes<F> for TracedHashing<'_, F> {
+    fn expr_hash(&self, ptr: &Ptr<F>) -> Option<ScalarPtr<F>> {
+        if let Some(scalar_ptr) = self.exprs.get(ptr) {
+            return Some(*scalar_ptr);
+        }
+        let scalar_ptr = self.hash(ptr)?;
+        self.exprs.insert(*ptr, scalar_ptr);
+        Some(scalar_ptr)
+    }
+
+    fn cont_hash(&self, ptr: &ContPtr<F>) -> Option<ScalarContPtr<F>> {
+        if let Some(scalar_ptr) = self.conts.get(ptr) {
+            return Some(*scalar_ptr);
+        }
+        let store = self.store;
+        let components = store.continuation_hash_components(self, &store.fetch_cont(ptr)?)?;
+        let scalar_ptr = ScalarContPtr::from_parts(ptr.0, self.cache.hash8(&components));
+        self.conts.insert(*ptr, scalar_ptr);
+        Some(scalar_ptr)
+    }
+}
+
+/// The hash of a commitment to `payload` with `secret`, computed through `cache`.
+fn commitment_hash_in<F: LurkField>(
+    cache: &PoseidonCache<F>,
+    secret: F,
+    payload: ScalarPtr<F>,
+) -> F {
+    cache.hash3(&[secret, payload.0.to_field(), payload.1])
+}
+
+/// Receives the values of `emit` as evaluation applies their `Emit` continuations. See `Store::with_emit_sink`.
+pub trait EmitSink<F: LurkField>: fmt::Debug + Send + Sync {
+    /// Called with the `ScalarPtr` of each emitted value, and its printed form if `wants_printed` is true.
+    fn emit(&self, value: ScalarPtr<F>, printed: Option<String>);
+
+    /// Whether `emit` receives printed forms. Printing is skipped otherwise.
+    fn wants_printed(&self) -> bool {
+        false
+    }
+
+    /// Whether `emit` is called at all. Emitted values are neither hashed nor printed otherwise.
+    fn is_enabled(&self) -> bool {
+        true
+    }
+}
+
+/// The default `EmitSink`, which discards everything.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopEmitSink;
+
+impl<F: LurkField> EmitSink<F> for NoopEmitSink {
+    fn emit(&self, _value: ScalarPtr<F>, _printed: Option<String>) {}
+
+    fn is_enabled(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -199,11 +634,22 @@ impl<F: LurkField, const N: usize> Hash for CacheKey<F, N> {
 }
 
 impl<F: LurkField> PoseidonCache<F> {
+    fn with_hasher(hasher: Arc<dyn LurkHasher<F>>) -> Self {
+        Self {
+            a3: Default::default(),
+            a4: Default::default(),
+            a6: Default::default(),
+            a8: Default::default(),
+            constants: HashConstants::constants_cache(),
+            hasher,
+        }
+    }
+
     fn hash3(&self, preimage: &[F; 3]) -> F {
         let hash = self
             .a3
             .entry(CacheKey(*preimage))
-            .or_insert_with(|| Poseidon::new_with_preimage(preimage, self.constants.c3()).hash());
+            .or_insert_with(|| self.hasher.hash3(preimage));
 
         *hash
     }
@@ -212,7 +658,7 @@ impl<F: LurkField> PoseidonCache<F> {
         let hash = self
             .a4
             .entry(CacheKey(*preimage))
-            .or_insert_with(|| Poseidon::new_with_preimage(preimage, self.constants.c4()).hash());
+            .or_insert_with(|| self.hasher.hash4(preimage));
 
         *hash
     }
@@ -221,7 +667,7 @@ impl<F: LurkField> PoseidonCache<F> {
         let hash = self
             .a6
             .entry(CacheKey(*preimage))
-            .or_insert_with(|| Poseidon::new_with_preimage(preimage, self.constants.c6()).hash());
+            .or_insert_with(|| self.hasher.hash6(preimage));
         *hash
     }
 
@@ -229,9 +675,90 @@ impl<F: LurkField> PoseidonCache<F> {
         let hash = self
             .a8
             .entry(CacheKey(*preimage))
-            .or_insert_with(|| Poseidon::new_with_preimage(preimage, self.constants.c8()).hash());
+            .or_insert_with(|| self.hasher.hash8(preimage));
         *hash
     }
+
+    fn hash_string(&self, s: &str) -> Option<F> {
+        self.hasher.hash_string(s)
+    }
+
+    fn clear(&self) {
+        self.a3.clear();
+        self.a4.clear();
+        self.a6.clear();
+        self.a8.clear();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.a3.is_empty() && self.a4.is_empty() && self.a6.is_empty() && self.a8.is_empty()
+    }
+}
+
+/// Parses big-endian hex digits, without a `0x` prefix, into a field element. Returns `None` if `hex` is not valid hex,
+/// is too long, or is not below the modulus.
+#[cfg(feature = "json")]
+fn field_from_hex<F: LurkField>(hex: &str) -> Option<F> {
+    let mut bytes = F::default().to_bytes();
+    if hex.is_empty() || hex.len() > 2 * bytes.len() || !hex.is_ascii() {
+        return None;
+    }
+    let padded = format!("{hex:0>width$}", width = 2 * bytes.len());
+    for (byte, digits) in bytes.iter_mut().rev().zip(padded.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+    }
+    F::from_bytes(&bytes)
+}
+
+/// Computes the uncached hash of `preimage`. This is Poseidon, unless the `sha` feature is enabled.
+#[cfg(not(feature = "sha"))]
+pub(crate) fn field_hash<'a, F: LurkField, A: neptune::Arity<F>>(
+    preimage: &[F],
+    constants: impl FnOnce() -> &'a PoseidonConstants<F, A>,
+) -> F {
+    Poseidon::new_with_preimage(preimage, constants()).hash()
+}
+
+/// Computes the uncached hash of `preimage`. With the `sha` feature enabled, the Poseidon constants are never built
+/// and the SHA-256 fallback is used instead.
+///
+/// WARNING: this changes the value of every `ScalarPtr` and cannot be proved by the circuit, which only knows Poseidon.
+#[cfg(feature = "sha")]
+pub(crate) fn field_hash<'a, F: LurkField, A: neptune::Arity<F>>(
+    preimage: &[F],
+    _constants: impl FnOnce() -> &'a PoseidonConstants<F, A>,
+) -> F {
+    sha256_hash(preimage)
+}
+
+/// Hashes the concatenated canonical representations of `preimage` with SHA-256, then reduces the digest into the
+/// field by clearing every bit at or above `F::CAPACITY`, so the little-endian value is always below the modulus.
+#[cfg(feature = "sha")]
+pub(crate) fn sha256_hash<F: LurkField>(preimage: &[F]) -> F {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    for f in preimage {
+        hasher.update(f.to_repr().as_ref());
+    }
+    let digest = hasher.finalize();
+
+    let mut repr = F::Repr::default();
+    {
+        let bytes = repr.as_mut();
+        let n = bytes.len().min(digest.len());
+        bytes[..n].copy_from_slice(&digest[..n]);
+
+        let capacity = F::CAPACITY as usize;
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            for bit in 0..8 {
+                if i * 8 + bit >= capacity {
+                    *byte &= !(1 << bit);
+                }
+            }
+        }
+    }
+    F::from_repr(repr).unwrap()
 }
 
 pub trait Object<F: LurkField>: fmt::Debug + Clone + PartialEq {
@@ -313,7 +840,7 @@ impl<F: LurkField> Pointer<F> for Ptr<F> {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(not(target_arch = "wasm32"), derive(Arbitrary))]
 // Note: the trait bound E: Tag is not necessary in the struct, but it makes the proptest strategy more efficient.
 /// A struct representing a scalar pointer with a tag and a value.
@@ -338,6 +865,16 @@ impl<E: Tag + Display, F: LurkField> Display for SPtr<E, F> {
     }
 }
 
+/// Prints the tag by name and the value in hex, e.g. `ScalarPtr { tag: Cons, value: 0x1a2b... }`.
+impl<E: Tag, F: LurkField> fmt::Debug for SPtr<E, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct(E::SCALAR_PTR_NAME)
+            .field("tag", &self.0)
+            .field("value", &format_args!("0x{}", self.1.hex_digits()))
+            .finish()
+    }
+}
+
 impl<E: Tag, F: LurkField> PartialOrd for SPtr<E, F> {
     fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         (
@@ -397,6 +934,12 @@ impl<E: Tag, F: LurkField> SPtr<E, F> {
     pub fn value(&self) -> &F {
         &self.1
     }
+
+    /// The value as a `u64`, if it fits: `to_repr` is little-endian, so this is its first 8 bytes, provided all the
+    /// others are zero. The tag is not checked, so this is only meaningful for pointers known to be nums or u64s.
+    pub fn value_as_u64(&self) -> Option<u64> {
+        self.1.to_u64()
+    }
 }
 
 impl<E: Tag, F: LurkField> Serialize for SPtr<E, F> {
@@ -473,6 +1016,12 @@ impl<F: LurkField> Pointer<F> for ContPtr<F> {
 }
 
 impl<F: LurkField> ContPtr<F> {
+    /// The simple continuations carry no data, so their pointers are the same in every store.
+    pub const OUTERMOST: Self = Self(ContTag::Outermost, RawPtr::new(0));
+    pub const ERROR: Self = Self(ContTag::Error, RawPtr::new(0));
+    pub const TERMINAL: Self = Self(ContTag::Terminal, RawPtr::new(0));
+    pub const DUMMY: Self = Self(ContTag::Dummy, RawPtr::new(0));
+
     pub const fn new(tag: ContTag, raw_ptr: RawPtr<F>) -> Self {
         Self(tag, raw_ptr)
     }
@@ -489,8 +1038,8 @@ impl<F: LurkField> ContPtr<F> {
 pub struct RawPtr<F: LurkField>((usize, bool), PhantomData<F>);
 
 impl<F: LurkField> RawPtr<F> {
-    fn new(p: usize) -> Self {
-        RawPtr((p, false), Default::default())
+    const fn new(p: usize) -> Self {
+        RawPtr((p, false), PhantomData)
     }
 
     const fn is_opaque(&self) -> bool {
@@ -523,6 +1072,8 @@ impl<F: LurkField> Hash for RawPtr<F> {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Expression<'a, F: LurkField> {
+    /// The empty list, which is also what the symbol `NIL` reads as by default. A store created with
+    /// `with_nil_as_symbol` reads `NIL` as an ordinary `Sym` instead; `Store::is_nil_symbol` tells that apart.
     Nil,
     Cons(Ptr<F>, Ptr<F>),
     Comm(F, Ptr<F>),
@@ -624,6 +1175,65 @@ impl<F: LurkField> Object<F> for Continuation<F> {
 }
 
 impl<F: LurkField> Continuation<F> {
+    /// The expressions this continuation references, and the continuation it continues with, if any.
+    fn children(&self) -> (Vec<Ptr<F>>, Option<ContPtr<F>>) {
+        match *self {
+            Self::Outermost | Self::Error | Self::Dummy | Self::Terminal => (Vec::new(), None),
+            Self::Call0 {
+                saved_env,
+                continuation,
+            }
+            | Self::Tail {
+                saved_env,
+                continuation,
+            }
+            | Self::Lookup {
+                saved_env,
+                continuation,
+            } => (vec![saved_env], Some(continuation)),
+            Self::Call {
+                unevaled_arg,
+                saved_env,
+                continuation,
+            } => (vec![unevaled_arg, saved_env], Some(continuation)),
+            Self::Call2 {
+                function,
+                saved_env,
+                continuation,
+            } => (vec![function, saved_env], Some(continuation)),
+            Self::Unop { continuation, .. } | Self::Emit { continuation } => {
+                (Vec::new(), Some(continuation))
+            }
+            Self::Binop {
+                saved_env,
+                unevaled_args,
+                continuation,
+                ..
+            } => (vec![saved_env, unevaled_args], Some(continuation)),
+            Self::Binop2 {
+                evaled_arg,
+                continuation,
+                ..
+            } => (vec![evaled_arg], Some(continuation)),
+            Self::If {
+                unevaled_args,
+                continuation,
+            } => (vec![unevaled_args], Some(continuation)),
+            Self::Let {
+                var,
+                body,
+                saved_env,
+                continuation,
+            }
+            | Self::LetRec {
+                var,
+                saved_env,
+                body,
+                continuation,
+            } => (vec![var, body, saved_env], Some(continuation)),
+        }
+    }
+
     pub(crate) fn intern_aux(&self, store: &mut Store<F>) -> ContPtr<F> {
         match self {
             Self::Outermost | Self::Dummy | Self::Error | Self::Terminal => {
@@ -780,9 +1390,10 @@ impl<F: LurkField> Continuation<F> {
     }
     pub fn get_simple_cont(&self) -> ContPtr<F> {
         match self {
-            Self::Outermost | Self::Dummy | Self::Error | Self::Terminal => {
-                ContPtr(self.cont_tag(), RawPtr::new(0))
-            }
+            Self::Outermost => ContPtr::OUTERMOST,
+            Self::Error => ContPtr::ERROR,
+            Self::Terminal => ContPtr::TERMINAL,
+            Self::Dummy => ContPtr::DUMMY,
 
             _ => unreachable!("Not a simple Continuation: {:?}", self),
         }
@@ -809,6 +1420,54 @@ impl<F: LurkField> TypePredicates for Ptr<F> {
 
 impl<F: LurkField> Default for Store<F> {
     fn default() -> Self {
+        Self::with_pair_hash(PairHash::default())
+    }
+}
+
+impl<F: LurkField> Store<F> {
+    /// Create a store hashing pairs of `ScalarPtr`s with `pair_hash`. Since this changes the `ScalarPtr` of every
+    /// cons, string and symbol, it is fixed at construction, and the symbols every store interns are hashed with it.
+    ///
+    /// WARNING: the circuit only knows `PairHash::Arity4`, so the results of a store using any other construction
+    /// cannot be proved.
+    pub fn with_pair_hash(pair_hash: PairHash) -> Self {
+        Self::with_hashing(pair_hash, Arc::new(PoseidonHasher::default()))
+    }
+
+    /// Create a store computing its hashes with `hasher` instead of Poseidon. Like `with_pair_hash`, this changes the
+    /// `ScalarPtr` of every expression; see `LurkHasher`.
+    pub fn with_hasher(hasher: Arc<dyn LurkHasher<F>>) -> Self {
+        Self::with_hashing(PairHash::default(), hasher)
+    }
+
+    /// Create a store passing every value evaluation emits to `sink`, so that output can be streamed as it is
+    /// produced rather than collected from the frames afterwards.
+    pub fn with_emit_sink(sink: Arc<dyn EmitSink<F>>) -> Self {
+        Self {
+            emit_sink: sink,
+            ..Default::default()
+        }
+    }
+
+    /// Pass `value` to the store's `EmitSink`, along with its printed form if the sink wants it. Values without a
+    /// `ScalarPtr`, which only opaque data lacking from the store can be, are skipped. Nothing is hashed if the sink is
+    /// not enabled.
+    pub fn emit(&self, value: &Ptr<F>) {
+        use crate::writer::Write;
+
+        if !self.emit_sink.is_enabled() {
+            return;
+        }
+        if let Some(scalar_ptr) = self.hash_expr(value) {
+            let printed = self
+                .emit_sink
+                .wants_printed()
+                .then(|| value.fmt_to_string(self));
+            self.emit_sink.emit(scalar_ptr, printed);
+        }
+    }
+
+    fn with_hashing(pair_hash: PairHash, hasher: Arc<dyn LurkHasher<F>>) -> Self {
         let mut store = Store {
             cons_store: Default::default(),
             comm_store: Default::default(),
@@ -832,20 +1491,33 @@ impl<F: LurkField> Default for Store<F> {
             opaque_map: Default::default(),
             scalar_ptr_map: Default::default(),
             scalar_ptr_cont_map: Default::default(),
-            poseidon_cache: Default::default(),
+            poseidon_cache: PoseidonCache::with_hasher(hasher),
             dehydrated: Default::default(),
             dehydrated_cont: Default::default(),
             opaque_raw_ptr_count: 0,
             pointer_scalar_ptr_cache: Default::default(),
             lurk_package: Arc::new(Package::lurk()),
             constants: Default::default(),
+            max_entries: None,
+            case_mode: CaseMode::default(),
+            abbreviate_quote: false,
+            wellknown_syms: Vec::with_capacity(LURK_EXTERNAL_SYMBOL_NAMES.len()),
+            vocabulary: Vec::new(),
+            vocabulary_names: Vec::new(),
+            sym_renames: Default::default(),
+            sym_rename_index: Default::default(),
+            nil_as_symbol: false,
+            pair_hash,
+            emit_sink: Arc::new(NoopEmitSink),
         };
 
         store.lurk_sym("");
 
-        for name in LURK_EXTERNAL_SYMBOL_NAMES {
-            store.lurk_sym(name);
+        for full_name in LURK_EXTERNAL_SYMBOL_FULL_NAMES.iter() {
+            let ptr = store.intern_static_sym(full_name);
+            store.wellknown_syms.push(ptr.1.idx());
         }
+        debug_assert!(store.wellknown_syms.windows(2).all(|w| w[0] < w[1]));
 
         {
             // Intern the root symbol.
@@ -866,9 +1538,145 @@ impl fmt::Display for Error {
     }
 }
 
-/// These methods provide a more ergonomic means of constructing and manipulating Lurk data.
-/// They can be thought of as a minimal DSL for working with Lurk data in Rust code.
-/// Prefer these methods when constructing literal data or assembling program fragments in
+/// Typed errors for store operations whose failure modes callers may want to match on.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum StoreError {
+    #[error("{store}[{index}] references dangling {tag} pointer at index {ptr_index}")]
+    DanglingPtr {
+        store: &'static str,
+        index: usize,
+        tag: String,
+        ptr_index: usize,
+    },
+    #[error("{store}[{index}] references {tag} pointer where {expected} was expected")]
+    TagMismatch {
+        store: &'static str,
+        index: usize,
+        tag: String,
+        expected: &'static str,
+    },
+    #[error("store capacity of {limit} entries exceeded")]
+    CapacityExceeded { limit: usize },
+    #[error("symbol {0} is not interned")]
+    UnknownSymbol(String),
+    #[error("symbol {0} is already interned")]
+    SymbolExists(String),
+    #[error("{0} is out of range for u64")]
+    OutOfRange(String),
+    #[error("invalid JSON expression: {0}")]
+    InvalidJson(String),
+    #[error("bytes are not the canonical representation of a field element")]
+    NonCanonicalBytes,
+    #[error("{store}[{index}] already holds a different entry")]
+    SlotOccupied { store: &'static str, index: usize },
+    #[error("entry for {store}[{index}] is already interned at index {existing}")]
+    InternedElsewhere {
+        store: &'static str,
+        index: usize,
+        existing: usize,
+    },
+    #[error("invalid light store: {0}")]
+    InvalidLightStore(String),
+    #[error("{field} of {cont} continuation is {tag} where {expected} was expected")]
+    InvalidContinuationField {
+        cont: String,
+        field: &'static str,
+        tag: String,
+        expected: &'static str,
+    },
+}
+
+/// The kind of expression a continuation field must hold, as checked by `Store::try_intern_cont`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FieldKind {
+    /// An environment: nil or a cons.
+    Env,
+    /// An argument list: nil or a cons.
+    List,
+    Sym,
+    Fun,
+    /// Any expression.
+    Expression,
+}
+
+impl FieldKind {
+    /// The kind's name in `StoreError::InvalidContinuationField`.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Env => "env",
+            Self::List => "list",
+            Self::Sym => "sym#",
+            Self::Fun => "fun#",
+            Self::Expression => "expression",
+        }
+    }
+
+    fn accepts(self, tag: ExprTag) -> bool {
+        match self {
+            Self::Env | Self::List => matches!(tag, ExprTag::Nil | ExprTag::Cons),
+            Self::Sym => tag == ExprTag::Sym,
+            Self::Fun => tag == ExprTag::Fun,
+            Self::Expression => true,
+        }
+    }
+}
+
+/// Raised by `Store::try_merge` when the stores were created by `Store::with_wellknown_syms` with different names.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("pre-interned symbol {position} is {ours} in this store but {theirs} in the other")]
+pub struct MergeConflict {
+    pub position: usize,
+    pub ours: String,
+    pub theirs: String,
+}
+
+/// Where `Store::try_merge` copied each entry of the merged store.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PtrRemap<F: LurkField> {
+    pub exprs: HashMap<Ptr<F>, Ptr<F>>,
+    pub conts: HashMap<ContPtr<F>, ContPtr<F>>,
+}
+
+impl<F: LurkField> PtrRemap<F> {
+    pub fn expr(&self, ptr: &Ptr<F>) -> Option<Ptr<F>> {
+        self.exprs.get(ptr).copied()
+    }
+
+    pub fn cont(&self, ptr: &ContPtr<F>) -> Option<ContPtr<F>> {
+        self.conts.get(ptr).copied()
+    }
+}
+
+/// Entry counts of one substore before and after `Store::gc`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SubstoreGcStats {
+    pub live: usize,
+    pub freed: usize,
+}
+
+/// What `Store::gc` reclaimed, per substore and in estimated bytes (see `Store::estimated_bytes`).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct GcStats {
+    pub substores: BTreeMap<&'static str, SubstoreGcStats>,
+    pub bytes_reclaimed: usize,
+}
+
+impl GcStats {
+    /// Total number of entries freed across all substores.
+    pub fn freed(&self) -> usize {
+        self.substores.values().map(|s| s.freed).sum()
+    }
+}
+
+impl From<StoreError> for Error {
+    fn from(e: StoreError) -> Self {
+        Error(e.to_string())
+    }
+}
+
+/// These methods provide a more ergonomic means of constructing and manipulating Lurk data.
+/// They can be thought of as a minimal DSL for working with Lurk data in Rust code.
+/// Prefer these methods when constructing literal data or assembling program fragments in
 /// tests or during evaluation, etc.
 impl<F: LurkField> Store<F> {
     pub fn nil(&mut self) -> Ptr<F> {
@@ -892,21 +1700,21 @@ impl<F: LurkField> Store<F> {
             .map(|c| Ptr(ExprTag::Comm, RawPtr::new(c)))
     }
 
+    /// Commit to `payload`, which may be any expression. The commitment hash is over `secret` and the payload's
+    /// `ScalarPtr`.
     pub fn hide(&mut self, secret: F, payload: Ptr<F>) -> Ptr<F> {
         self.intern_comm(secret, payload)
     }
 
+    /// Open the commitment `ptr` (a `Comm`, or a `Num` holding a commitment hash), returning its secret and the
+    /// original payload. A payload known to the store only by its hash is returned as an opaque `Ptr`. Returns `None`
+    /// if the commitment itself is unknown.
     pub fn open(&self, ptr: Ptr<F>) -> Option<(F, Ptr<F>)> {
         let p = match ptr.0 {
             ExprTag::Comm => ptr,
             ExprTag::Num => {
-                let scalar = self.fetch_num(&ptr).map(|x| x.into_scalar()).unwrap();
-                match self.get_maybe_opaque(ExprTag::Comm, scalar) {
-                    Some(c) => c,
-                    None => {
-                        panic!("Can't find commitment in the store.")
-                    }
-                }
+                let scalar = self.fetch_num(&ptr).map(|x| x.into_scalar())?;
+                self.get_maybe_opaque(ExprTag::Comm, scalar)?
             }
             _ => return None,
         };
@@ -983,11 +1791,40 @@ impl<F: LurkField> Store<F> {
         self.intern_sym_with_case_conversion(name, &package)
     }
 
+    /// Intern a symbol by its canonical full name, such as `.LURK.LAMBDA`, skipping the case conversion and package
+    /// resolution of `sym` and `lurk_sym`, and the parsing of the name into a `Sym`: its path segments are split
+    /// directly from the name, which must already be canonical and escape no characters.
+    pub(crate) fn intern_static_sym(&mut self, full_name: &'static str) -> Ptr<F> {
+        debug_assert_eq!(
+            Sym::new_absolute(full_name.into()).path(),
+            &full_name.split('.').collect::<Vec<_>>()
+        );
+        self.intern_sym_with_path(full_name, full_name.split('.'))
+    }
+
     pub fn sym<T: AsRef<str>>(&mut self, name: T) -> Ptr<F> {
         let package = Default::default();
         self.intern_sym_with_case_conversion(name, &package)
     }
 
+    /// Which of the well-known Lurk symbols `ptr` is, if any. This is an index comparison, not a string comparison.
+    pub fn is_wellknown_sym(&self, ptr: &Ptr<F>) -> Option<WellKnown> {
+        if ptr.is_opaque() || !matches!(ptr.tag(), ExprTag::Sym | ExprTag::Nil) {
+            return None;
+        }
+        let i = self.wellknown_syms.binary_search(&ptr.1.idx()).ok()?;
+        WellKnown::ALL.get(i).copied()
+    }
+
+    /// Intern each of `names` as by `sym`, returning their pointers in order.
+    pub fn intern_syms<T: AsRef<str>>(&mut self, names: &[T]) -> Vec<Ptr<F>> {
+        let package = Package::default();
+        names
+            .iter()
+            .map(|name| self.intern_sym_with_case_conversion(name, &package))
+            .collect()
+    }
+
     pub fn key<T: AsRef<str>>(&mut self, name: T) -> Ptr<F> {
         self.root_sym(name, true)
     }
@@ -1013,7 +1850,7 @@ impl<F: LurkField> Store<F> {
         Ok(self.car_cdr(expr)?.1)
     }
 
-    pub(crate) const fn poseidon_constants(&self) -> &HashConstants<F> {
+    pub(crate) fn poseidon_constants(&self) -> &HashConstants<F> {
         &self.poseidon_cache.constants
     }
 
@@ -1021,12 +1858,516 @@ impl<F: LurkField> Store<F> {
         Store::default()
     }
 
+    /// Create a store whose `try_intern_*` methods fail with `StoreError::CapacityExceeded` instead of growing past
+    /// `limit` primary entries (see `entry_count`). The symbols interned by `default` count towards the limit. The
+    /// infallible `intern_*` methods ignore the limit.
+    pub fn with_max_entries(limit: usize) -> Self {
+        Self {
+            max_entries: Some(limit),
+            ..Self::default()
+        }
+    }
+
+    /// Load a store from the serialized `LightStore` in `bytes`, as written by `LightStore::ser`. The store is
+    /// validated before it is returned.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Self::from_reader(bytes)
+    }
+
+    /// Load a store from a serialized `LightStore` read from `r`, as `from_bytes`. Entries are decoded, checked,
+    /// interned and validated one at a time as they are read, so neither the serialization nor the decoded
+    /// `LightStore` is ever held in memory, and reading stops at the first invalid entry.
+    pub fn from_reader<R: std::io::Read>(mut r: R) -> Result<Self, StoreError> {
+        let invalid = |e: anyhow::Error| StoreError::InvalidLightStore(e.to_string());
+        let entry_count = LightData::de_cell_header_reader(&mut r).map_err(invalid)?;
+        let mut loader = LightStoreLoader::new();
+        for _ in 0..entry_count {
+            let entry = LightData::de_reader(&mut r).map_err(invalid)?;
+            let (ptr, expr) =
+                <(ScalarPtr<F>, Option<LightExpr<F>>)>::de(&entry).map_err(invalid)?;
+            loader.add(ptr, expr).map_err(invalid)?;
+        }
+        loader.finish().map_err(invalid)
+    }
+
+    /// Create a store that, after the symbols every store interns, interns each of `names` as by `sym`, in order. Stores
+    /// created with the same `names` assign them the same pointers, so language variants built on Lurk can share
+    /// index assignments. `is_wellknown_sym` still only recognizes the Lurk symbols, but `try_merge` refuses to merge
+    /// stores created with different `names`.
+    pub fn with_wellknown_syms(names: &[&str]) -> Self {
+        let mut store = Self::default();
+        let ptrs = store.intern_syms(names);
+        store.vocabulary = ptrs.iter().map(|ptr| ptr.1.idx()).collect();
+        store.vocabulary_names = ptrs
+            .iter()
+            .map(|ptr| store.fetch_sym(ptr).unwrap().full_sym_name())
+            .collect();
+        store
+    }
+
+    /// Create a store whose conses are kept in an append-only arena rather than deduplicated: `intern_cons` does not
+    /// hash its arguments, and interning equal conses twice yields distinct pointers. Hashing and fetching are
+    /// unaffected, but pointer equality no longer implies structural equality for conses; see `dedups_conses`.
+    pub fn with_cons_arena() -> Self {
+        Self {
+            cons_store: Substore::Arena(Vec::new()),
+            ..Self::default()
+        }
+    }
+
+    /// Whether equal conses are deduplicated, i.e. whether this store was not created with `with_cons_arena`.
+    pub fn dedups_conses(&self) -> bool {
+        matches!(self.cons_store, Substore::Set(_))
+    }
+
+    /// Create a store in which interning or reading the name `NIL` yields an ordinary symbol, tagged `Sym`, instead of
+    /// nil. Nil itself is still available from `nil`, `intern_nil` and `get_nil`. It shares the symbol's interned
+    /// name, but hashes differently because of its tag.
+    pub fn with_nil_as_symbol(nil_as_symbol: bool) -> Self {
+        Self {
+            nil_as_symbol,
+            ..Self::default()
+        }
+    }
+
+    /// Total number of primary entries: expressions, symbols, strings and continuations.
+    pub fn entry_count(&self) -> usize {
+        self.cons_store.len()
+            + self.comm_store.len()
+            + self.fun_store.len()
+            + self.sym_store.0.len()
+            + self.num_store.len()
+            + self.str_store.0.len()
+            + self.thunk_store.len()
+            + self.call0_store.len()
+            + self.call_store.len()
+            + self.call2_store.len()
+            + self.tail_store.len()
+            + self.lookup_store.len()
+            + self.unop_store.len()
+            + self.binop_store.len()
+            + self.binop2_store.len()
+            + self.if_store.len()
+            + self.let_store.len()
+            + self.letrec_store.len()
+            + self.emit_store.len()
+    }
+
+    /// The name, entry count and estimated size in bytes of each substore. Interned strings are counted by their size
+    /// in the interner's buffer; the caches and reverse maps are not included.
+    fn substore_sizes(&self) -> [(&'static str, usize, usize); 19] {
+        fn sized<T>(name: &'static str, set: &IndexSet<T>) -> (&'static str, usize, usize) {
+            (name, set.len(), set.len() * std::mem::size_of::<T>())
+        }
+        fn substore<T: Hash + Eq>(
+            name: &'static str,
+            substore: &Substore<T>,
+        ) -> (&'static str, usize, usize) {
+            let len = substore.len();
+            (name, len, len * std::mem::size_of::<T>())
+        }
+        fn strings(name: &'static str, set: &StringSet) -> (&'static str, usize, usize) {
+            (name, set.0.len(), set.buffer_bytes())
+        }
+        [
+            substore("cons_store", &self.cons_store),
+            sized("comm_store", &self.comm_store),
+            sized("fun_store", &self.fun_store),
+            strings("sym_store", &self.sym_store),
+            sized("num_store", &self.num_store),
+            strings("str_store", &self.str_store),
+            sized("thunk_store", &self.thunk_store),
+            sized("call0_store", &self.call0_store),
+            sized("call_store", &self.call_store),
+            sized("call2_store", &self.call2_store),
+            sized("tail_store", &self.tail_store),
+            sized("lookup_store", &self.lookup_store),
+            sized("unop_store", &self.unop_store),
+            sized("binop_store", &self.binop_store),
+            sized("binop2_store", &self.binop2_store),
+            sized("if_store", &self.if_store),
+            sized("let_store", &self.let_store),
+            sized("letrec_store", &self.letrec_store),
+            sized("emit_store", &self.emit_store),
+        ]
+    }
+
+    /// Approximate bytes used by the symbol and string interners' buffers, respectively.
+    pub fn interner_bytes(&self) -> (usize, usize) {
+        (self.sym_store.buffer_bytes(), self.str_store.buffer_bytes())
+    }
+
+    /// Bit-length of the largest interned num, taken as its canonical representative in `[0, p)`: 0 if every num is
+    /// zero or none is interned. Negative nums are large field elements, so they need nearly the full field. Hashes are
+    /// not considered, as they always use the full field.
+    pub fn max_value_bits(&self) -> usize {
+        self.num_store
+            .iter()
+            .map(|num| match num {
+                Num::U64(n) => (u64::BITS - n.leading_zeros()) as usize,
+                Num::Scalar(s) => {
+                    // `to_repr` is little-endian.
+                    let repr = s.to_repr();
+                    let bytes = repr.as_ref();
+                    bytes.iter().rposition(|b| *b != 0).map_or(0, |i| {
+                        8 * i + (u8::BITS - bytes[i].leading_zeros()) as usize
+                    })
+                }
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// A rough estimate of the memory held by the store's entries, in bytes.
+    pub fn estimated_bytes(&self) -> usize {
+        self.substore_sizes()
+            .iter()
+            .map(|(_, _, bytes)| bytes)
+            .sum()
+    }
+
+    /// Collect garbage, keeping only the entries reachable from `roots` (and the symbols every store interns). The
+    /// store is rebuilt, so all other pointers into it are invalidated; `roots` are updated in place to point into the
+    /// rebuilt store. Continuations are only kept when reachable from a thunk. The Poseidon cache is kept, but the
+    /// rebuilt entries are dehydrated.
+    pub fn gc(&mut self, roots: &mut [Ptr<F>]) -> GcStats {
+        let before = self.substore_sizes();
+        let before_bytes = self.estimated_bytes();
+
+        let mut copier = Copier::new(self);
+        for root in roots.iter_mut() {
+            *root = copier.copy_expr(root);
+        }
+        let mut to = copier.to;
+        to.poseidon_cache = std::mem::take(&mut self.poseidon_cache);
+        *self = to;
+
+        let substores = before
+            .iter()
+            .zip(self.substore_sizes())
+            .map(|((name, before, _), (_, live, _))| {
+                let freed = before.saturating_sub(live);
+                (*name, SubstoreGcStats { live, freed })
+            })
+            .collect();
+        GcStats {
+            substores,
+            bytes_reclaimed: before_bytes.saturating_sub(self.estimated_bytes()),
+        }
+    }
+
+    /// Pointers to every entry of the continuation substores, substore by substore. Simple continuations, which have
+    /// no substore, are not included.
+    fn cont_ptrs(&self) -> impl Iterator<Item = ContPtr<F>> + '_ {
+        let cont = |tag, len| (0..len).map(move |idx| ContPtr(tag, RawPtr::new(idx)));
+        cont(ContTag::Call0, self.call0_store.len())
+            .chain(cont(ContTag::Call, self.call_store.len()))
+            .chain(cont(ContTag::Call2, self.call2_store.len()))
+            .chain(cont(ContTag::Tail, self.tail_store.len()))
+            .chain(cont(ContTag::Lookup, self.lookup_store.len()))
+            .chain(cont(ContTag::Unop, self.unop_store.len()))
+            .chain(cont(ContTag::Binop, self.binop_store.len()))
+            .chain(cont(ContTag::Binop2, self.binop2_store.len()))
+            .chain(cont(ContTag::If, self.if_store.len()))
+            .chain(cont(ContTag::Let, self.let_store.len()))
+            .chain(cont(ContTag::LetRec, self.letrec_store.len()))
+            .chain(cont(ContTag::Emit, self.emit_store.len()))
+    }
+
+    /// The hash components, as by `get_hash_components_cont`, of every entry of the continuation substores, computed
+    /// in parallel. Entries whose components cannot be computed, because they reference dangling pointers, are
+    /// skipped.
+    pub fn cont_components_iter(&self) -> impl ParallelIterator<Item = [F; 8]> + '_ {
+        self.cont_ptrs()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .filter_map(|ptr| self.get_hash_components_cont(&ptr))
+    }
+
+    /// Copy everything reachable from `exprs` and `conts` into a new store configured like this one. Continuations
+    /// are followed into both the expressions and the continuations they reference. Entries shared between roots are
+    /// copied once, and the returned maps give the new pointer of every copied pointer, roots included.
+    pub fn extract_reachable_multi(
+        &self,
+        exprs: &[Ptr<F>],
+        conts: &[ContPtr<F>],
+    ) -> (
+        Store<F>,
+        HashMap<Ptr<F>, Ptr<F>>,
+        HashMap<ContPtr<F>, ContPtr<F>>,
+    ) {
+        let mut copier = Copier::new(self);
+        for expr in exprs {
+            copier.copy_expr(expr);
+        }
+        for cont in conts {
+            copier.copy_cont(cont);
+        }
+        (copier.to, copier.exprs, copier.conts)
+    }
+
+    /// A store configured like this one, holding only what every store interns.
+    fn empty_like(&self) -> Store<F> {
+        Store {
+            max_entries: self.max_entries,
+            case_mode: self.case_mode,
+            abbreviate_quote: self.abbreviate_quote,
+            nil_as_symbol: self.nil_as_symbol,
+            lurk_package: self.lurk_package.clone(),
+            emit_sink: self.emit_sink.clone(),
+            cons_store: match self.cons_store {
+                Substore::Set(_) => Substore::Set(Default::default()),
+                Substore::Arena(_) => Substore::Arena(Vec::new()),
+            },
+            ..Store::with_hashing(self.pair_hash, self.poseidon_cache.hasher.clone())
+        }
+    }
+
+    /// Discard every entry and cache, keeping the store's configuration: afterwards the store holds what a new store
+    /// does, at the same indices. Symbols pre-interned by `with_wellknown_syms` are interned again under the names
+    /// they were created with, even if renamed since, so they also keep their indices. Pointers obtained before the
+    /// reset, other than to these symbols, must not be used after it.
+    pub fn reset(&mut self) {
+        let mut store = self.empty_like();
+        store.vocabulary = self
+            .vocabulary_names
+            .iter()
+            .map(|name| store.intern_sym_by_full_name(name).1.idx())
+            .collect();
+        debug_assert_eq!(self.vocabulary, store.vocabulary);
+        store.vocabulary_names = std::mem::take(&mut self.vocabulary_names);
+        *self = store;
+    }
+
+    /// Copy every entry of `other` into this store, returning the pointer each was copied to. Entries this store already
+    /// holds are reused. Fails, leaving this store unchanged, if both stores were created by `with_wellknown_syms` and
+    /// disagree on a symbol both pre-interned: pointers to such symbols are meant to be interchangeable between the
+    /// stores, so merging would silently give them different meanings.
+    pub fn try_merge(&mut self, other: &Store<F>) -> Result<PtrRemap<F>, MergeConflict> {
+        let name = |store: &Store<F>, idx| {
+            store
+                .sym_name_cow(&Ptr(ExprTag::Sym, RawPtr::new(idx)))
+                .map_or_else(String::new, Cow::into_owned)
+        };
+        for (position, (&ours, &theirs)) in
+            self.vocabulary.iter().zip(&other.vocabulary).enumerate()
+        {
+            let (ours, theirs) = (name(self, ours), name(other, theirs));
+            if ours != theirs {
+                return Err(MergeConflict {
+                    position,
+                    ours,
+                    theirs,
+                });
+            }
+        }
+
+        let expr = |tag, idx| Ptr(tag, RawPtr::new(idx));
+        let ptrs = |tag, len| (0..len).map(move |idx| expr(tag, idx));
+        let syms = other.sym_store.0.into_iter().map(|(symbol, _)| symbol);
+        let strs = other.str_store.0.into_iter().map(|(symbol, _)| symbol);
+        let exprs: Vec<Ptr<F>> = ptrs(ExprTag::Cons, other.cons_store.len())
+            .chain(ptrs(ExprTag::Comm, other.comm_store.len()))
+            .chain(ptrs(ExprTag::Fun, other.fun_store.len()))
+            .chain(ptrs(ExprTag::Num, other.num_store.len()))
+            .chain(ptrs(ExprTag::Thunk, other.thunk_store.len()))
+            .chain(syms.map(|symbol| expr(ExprTag::Sym, symbol.to_usize())))
+            .chain(strs.map(|symbol| expr(ExprTag::Str, symbol.to_usize())))
+            .chain(other.opaque_map.iter().map(|entry| *entry.key()))
+            .collect();
+
+        let mut copier = Copier {
+            from: other,
+            to: std::mem::take(self),
+            exprs: Default::default(),
+            conts: Default::default(),
+        };
+        for ptr in &exprs {
+            copier.copy_expr(ptr);
+        }
+        for ptr in other.cont_ptrs() {
+            copier.copy_cont(&ptr);
+        }
+        *self = copier.to;
+        Ok(PtrRemap {
+            exprs: copier.exprs,
+            conts: copier.conts,
+        })
+    }
+
+    /// A copy of the store whose entries are interned in a canonical order, so that stores holding the same entries
+    /// canonicalize to stores with identical indices, whatever order the entries were originally interned in. All
+    /// expressions and continuations are visited in order of their scalar pointers and copied depth-first. Keywords
+    /// not referenced by any other entry are copied as symbols, which share their interner entries.
+    pub fn canonicalize(&self) -> Store<F> {
+        let expr = |tag, idx| Ptr(tag, RawPtr::new(idx));
+        let ptrs = |tag, len| (0..len).map(move |idx| expr(tag, idx));
+        let syms = self.sym_store.0.into_iter().map(|(symbol, _)| symbol);
+        let strs = self.str_store.0.into_iter().map(|(symbol, _)| symbol);
+
+        let mut exprs: Vec<(ScalarPtr<F>, Ptr<F>)> = ptrs(ExprTag::Cons, self.cons_store.len())
+            .chain(ptrs(ExprTag::Comm, self.comm_store.len()))
+            .chain(ptrs(ExprTag::Fun, self.fun_store.len()))
+            .chain(ptrs(ExprTag::Num, self.num_store.len()))
+            .chain(ptrs(ExprTag::Thunk, self.thunk_store.len()))
+            .chain(syms.map(|symbol| expr(ExprTag::Sym, symbol.to_usize())))
+            .chain(strs.map(|symbol| expr(ExprTag::Str, symbol.to_usize())))
+            .chain(self.opaque_map.iter().map(|entry| *entry.key()))
+            .filter_map(|ptr| Some((self.hash_expr(&ptr)?, ptr)))
+            .collect();
+        // Opaque pointers share their scalar pointers with the expressions they stand for.
+        exprs.sort_by(|(a, a_ptr), (b, b_ptr)| {
+            a.cmp(b).then(a_ptr.is_opaque().cmp(&b_ptr.is_opaque()))
+        });
+
+        let mut conts: Vec<(ScalarContPtr<F>, ContPtr<F>)> = self
+            .cont_ptrs()
+            .filter_map(|ptr| Some((self.hash_cont(&ptr)?, ptr)))
+            .collect();
+        conts.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut copier = Copier::new(self);
+        for (_, ptr) in &exprs {
+            copier.copy_expr(ptr);
+        }
+        for (_, ptr) in &conts {
+            copier.copy_cont(ptr);
+        }
+        copier.to
+    }
+
+    fn check_capacity(&self) -> Result<(), StoreError> {
+        match self.max_entries {
+            Some(limit) if self.entry_count() >= limit => {
+                Err(StoreError::CapacityExceeded { limit })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Like `intern_cons`, but fails instead of adding a new entry to a store which is at capacity.
+    pub fn try_intern_cons(&mut self, car: Ptr<F>, cdr: Ptr<F>) -> Result<Ptr<F>, StoreError> {
+        if self.cons_store.would_insert(&(car, cdr)) {
+            self.check_capacity()?;
+        }
+        Ok(self.intern_cons(car, cdr))
+    }
+
+    /// Intern the cons `(car . cdr)` at index `index` of `cons_store`, so that stores can agree on cons indices without
+    /// exchanging their contents. If the store holds fewer conses, it is first padded with placeholder lists of nils,
+    /// `(nil)`, `(nil nil)` and so on, which are ordinary conses. When `car` is nil, the placeholders are lists of `t`s
+    /// instead, so that none of them can be the requested cons. Fails if the slot holds a different cons, if the cons
+    /// is already interned at another index, or if the store reaches capacity.
+    pub fn intern_cons_with_index(
+        &mut self,
+        index: usize,
+        car: Ptr<F>,
+        cdr: Ptr<F>,
+    ) -> Result<Ptr<F>, StoreError> {
+        let ptr = Ptr(ExprTag::Cons, RawPtr::new(index));
+        if let Some(entry) = self.cons_store.get_index(index) {
+            return if *entry == (car, cdr) {
+                Ok(ptr)
+            } else {
+                Err(StoreError::SlotOccupied {
+                    store: "cons_store",
+                    index,
+                })
+            };
+        }
+        if let Substore::Set(set) = &self.cons_store {
+            if let Some(existing) = set.get_index_of(&(car, cdr)) {
+                return Err(StoreError::InternedElsewhere {
+                    store: "cons_store",
+                    index,
+                    existing,
+                });
+            }
+        }
+
+        let nil = self.intern_nil();
+        let filler = if car == nil { self.t() } else { nil };
+        let mut placeholder = nil;
+        while self.cons_store.len() < index {
+            placeholder = self.try_intern_cons(filler, placeholder)?;
+        }
+        let interned = self.try_intern_cons(car, cdr)?;
+        debug_assert_eq!(ptr, interned);
+        Ok(interned)
+    }
+
+    /// Like `intern_comm`, but fails instead of adding a new entry to a store which is at capacity.
+    pub fn try_intern_comm(&mut self, secret: F, payload: Ptr<F>) -> Result<Ptr<F>, StoreError> {
+        if !self.comm_store.contains(&(FWrap(secret), payload)) {
+            self.check_capacity()?;
+        }
+        Ok(self.intern_comm(secret, payload))
+    }
+
+    /// Like `intern_fun`, but fails instead of adding a new entry to a store which is at capacity.
+    pub fn try_intern_fun(
+        &mut self,
+        arg: Ptr<F>,
+        body: Ptr<F>,
+        closed_env: Ptr<F>,
+    ) -> Result<Ptr<F>, StoreError> {
+        if !self.fun_store.contains(&(arg, body, closed_env)) {
+            self.check_capacity()?;
+        }
+        Ok(self.intern_fun(arg, body, closed_env))
+    }
+
+    /// Like `intern_num`, but fails instead of adding a new entry to a store which is at capacity.
+    pub fn try_intern_num<T: Into<Num<F>>>(&mut self, num: T) -> Result<Ptr<F>, StoreError> {
+        let num = num.into();
+        if self.get_num(num).is_none() {
+            self.check_capacity()?;
+        }
+        Ok(self.intern_num(num))
+    }
+
+    /// Like `intern_thunk`, but fails instead of adding a new entry to a store which is at capacity.
+    pub fn try_intern_thunk(&mut self, thunk: Thunk<F>) -> Result<Ptr<F>, StoreError> {
+        if !self.thunk_store.contains(&thunk) {
+            self.check_capacity()?;
+        }
+        Ok(self.intern_thunk(thunk))
+    }
+
+    /// How `read` converts the case of unquoted symbol names. `sym` and `lurk_sym` name canonical symbols and always
+    /// convert to upper case, regardless of this setting.
+    pub fn case_mode(&self) -> CaseMode {
+        self.case_mode
+    }
+
+    pub fn set_case_mode(&mut self, case_mode: CaseMode) {
+        self.case_mode = case_mode;
+    }
+
+    /// Whether the writer prints `(quote x)` as `'x`. Off by default, so the raw form is printed.
+    pub fn abbreviate_quote(&self) -> bool {
+        self.abbreviate_quote
+    }
+
+    pub fn set_abbreviate_quote(&mut self, abbreviate_quote: bool) {
+        self.abbreviate_quote = abbreviate_quote;
+    }
+
     pub fn intern_nil(&mut self) -> Ptr<F> {
-        self.lurk_sym("nil")
+        let nil = self.lurk_sym("nil");
+        Ptr(ExprTag::Nil, nil.1)
     }
 
     pub fn get_nil(&self) -> Ptr<F> {
-        self.get_lurk_sym("nil", true).expect("missing NIL")
+        let nil = self.get_lurk_sym("nil", true).expect("missing NIL");
+        Ptr(ExprTag::Nil, nil.1)
+    }
+
+    /// Whether `ptr` is the symbol `LURK.NIL` tagged `Sym`, as opposed to nil itself. Such a symbol only arises in a
+    /// store created with `with_nil_as_symbol`: otherwise the name `NIL` always interns as the `Nil`-tagged pointer,
+    /// for which this returns false. Opaque pointers are not resolved.
+    pub fn is_nil_symbol(&self, ptr: &Ptr<F>) -> bool {
+        ptr.0 == ExprTag::Sym && !ptr.is_opaque() && ptr.1 == self.get_nil().1
     }
 
     pub fn get_begin(&self) -> Ptr<F> {
@@ -1042,6 +2383,11 @@ impl<F: LurkField> Store<F> {
     }
 
     pub fn intern_cons(&mut self, car: Ptr<F>, cdr: Ptr<F>) -> Ptr<F> {
+        self.intern_cons_full(car, cdr).0
+    }
+
+    /// Like `intern_cons`, but also tells whether the cons was new rather than already interned.
+    fn intern_cons_full(&mut self, car: Ptr<F>, cdr: Ptr<F>) -> (Ptr<F>, bool) {
         if car.is_opaque() || cdr.is_opaque() {
             self.hash_expr(&car);
             self.hash_expr(&cdr);
@@ -1052,7 +2398,22 @@ impl<F: LurkField> Store<F> {
         if inserted {
             self.dehydrated.push(ptr);
         }
-        ptr
+        (ptr, inserted)
+    }
+
+    /// Intern a cons for each of `pairs`, returning their pointers in order and how many of them were new. The rest
+    /// were already interned, possibly earlier in `pairs`. In a store created with `with_cons_arena` every cons is new.
+    pub fn intern_conses(&mut self, pairs: &[(Ptr<F>, Ptr<F>)]) -> (Vec<Ptr<F>>, usize) {
+        let mut new = 0;
+        let ptrs = pairs
+            .iter()
+            .map(|&(car, cdr)| {
+                let (ptr, inserted) = self.intern_cons_full(car, cdr);
+                new += usize::from(inserted);
+                ptr
+            })
+            .collect();
+        (ptrs, new)
     }
 
     pub fn intern_strcons(&mut self, car: Ptr<F>, cdr: Ptr<F>) -> Ptr<F> {
@@ -1106,6 +2467,17 @@ impl<F: LurkField> Store<F> {
         None
     }
 
+    /// The hash `ptr` was interned with, if it is opaque. Concrete pointers, whose contents `fetch` returns, have
+    /// `None`; use `hash_expr` for their hashes.
+    pub fn opaque_hash(&self, ptr: &Ptr<F>) -> Option<F> {
+        if !ptr.is_opaque() {
+            return None;
+        }
+        self.opaque_map
+            .get(ptr)
+            .map(|scalar_ptr| *scalar_ptr.value())
+    }
+
     // Intern a potentially-opaque value. If the corresponding non-opaque value is already known to the store, and
     // `return_non_opaque_if_existing` is true, return the known value.
     fn intern_opaque_aux(
@@ -1251,6 +2623,9 @@ impl<F: LurkField> Store<F> {
         }
     }
 
+    /// Intern the expression `scalar_ptr` stands for in `scalar_store`, with its children. Every `ScalarPtr` interned
+    /// is recorded in the reverse map and looked up there first, so a subexpression shared in `scalar_store` is
+    /// interned once and shared by every parent, even in a store that does not deduplicate conses.
     pub fn intern_scalar_ptr(
         &mut self,
         scalar_ptr: ScalarPtr<F>,
@@ -1371,7 +2746,82 @@ impl<F: LurkField> Store<F> {
     pub fn intern_list(&mut self, elts: &[Ptr<F>]) -> Ptr<F> {
         elts.iter()
             .rev()
-            .fold(self.lurk_sym("nil"), |acc, elt| self.intern_cons(*elt, acc))
+            .fold(self.intern_nil(), |acc, elt| self.intern_cons(*elt, acc))
+    }
+
+    /// Like `intern_list`, for elements produced by an iterator of unknown length. An empty iterator yields nil.
+    pub fn intern_list_from_iter<I: Iterator<Item = Ptr<F>>>(&mut self, iter: I) -> Ptr<F> {
+        let elts: Vec<_> = iter.collect();
+        if elts.is_empty() {
+            return self.intern_nil();
+        }
+        self.intern_list(&elts)
+    }
+
+    /// Intern the list of `elts` terminated by `tail` instead of nil, e.g. `(1 2 . 3)`.
+    pub fn intern_improper_list(&mut self, elts: &[Ptr<F>], tail: Ptr<F>) -> Ptr<F> {
+        elts.iter()
+            .rev()
+            .fold(tail, |acc, elt| self.intern_cons(*elt, acc))
+    }
+
+    /// Lazily iterate over the elements of `list`. Iteration stops at the first tail that is not a cons, which
+    /// `ListIter::improper_tail` reports unless it is nil.
+    pub fn list_iter(&self, list: Ptr<F>) -> ListIter<'_, F> {
+        ListIter {
+            store: self,
+            rest: Some(list),
+            improper_tail: None,
+        }
+    }
+
+    /// Whether `env` is a well-formed environment: a nil-terminated list whose elements are bindings `(var . value)`
+    /// with a symbol `var`, or recursive frames, as built by `letrec`, which are non-empty nil-terminated lists of
+    /// such bindings. Opaque conses cannot be inspected, so an environment containing one is rejected.
+    pub fn is_valid_env(&self, env: &Ptr<F>) -> bool {
+        let is_binding = |ptr: &Ptr<F>| {
+            ptr.tag() == ExprTag::Cons
+                && !ptr.is_opaque()
+                && matches!(self.fetch_cons(ptr), Some((var, _)) if var.tag() == ExprTag::Sym)
+        };
+        let is_frame = |ptr: &Ptr<F>| {
+            let mut bindings = self.list_iter(*ptr);
+            let mut len = 0;
+            for binding in bindings.by_ref() {
+                if !is_binding(&binding) {
+                    return false;
+                }
+                len += 1;
+            }
+            len > 0 && bindings.improper_tail().is_none()
+        };
+
+        if !matches!(env.tag(), ExprTag::Nil | ExprTag::Cons) {
+            return false;
+        }
+        let mut entries = self.list_iter(*env);
+        for entry in entries.by_ref() {
+            if !is_binding(&entry) && !is_frame(&entry) {
+                return false;
+            }
+        }
+        entries.improper_tail().is_none()
+    }
+
+    /// Whether `ptr` is a proper list, and of what length, an improper list, or not a list at all. Nil is the proper
+    /// list of length 0. A cons whose cdr chain reaches an opaque cons counts as improper, since its end is unknown.
+    pub fn list_kind(&self, ptr: &Ptr<F>) -> ListKind {
+        match ptr.tag() {
+            ExprTag::Nil | ExprTag::Cons => {
+                let mut iter = self.list_iter(*ptr);
+                let len = iter.by_ref().count();
+                match iter.improper_tail() {
+                    Some(_) => ListKind::Improper,
+                    None => ListKind::Proper(len),
+                }
+            }
+            _ => ListKind::Atom,
+        }
     }
 
     pub fn intern_sym_with_case_conversion<T: AsRef<str>>(
@@ -1380,7 +2830,7 @@ impl<F: LurkField> Store<F> {
         package: &Package,
     ) -> Ptr<F> {
         let mut name = name.as_ref().to_string();
-        convert_sym_case(&mut name);
+        convert_sym_case(&mut name, CaseMode::Upper);
         let sym = Sym::new_absolute(name);
 
         self.intern_sym_in_package(sym, package)
@@ -1399,9 +2849,14 @@ impl<F: LurkField> Store<F> {
     }
 
     fn get_sym_by_full_name<T: AsRef<str>>(&self, name: T) -> Ptr<F> {
+        self.try_get_sym_by_full_name(name).unwrap()
+    }
+
+    /// Like `get_sym_by_full_name`, but returns `None` if the symbol is not interned.
+    fn try_get_sym_by_full_name<T: AsRef<str>>(&self, name: T) -> Option<Ptr<F>> {
         let name = name.as_ref();
 
-        let (tag, symbol_name) = if name == ".LURK.NIL" {
+        let (tag, symbol_name) = if name == ".LURK.NIL" && !self.nil_as_symbol {
             (ExprTag::Nil, "LURK.NIL")
         } else {
             let (names_keyword, symbol_name) = names_keyword(name);
@@ -1413,19 +2868,25 @@ impl<F: LurkField> Store<F> {
             }
         };
 
-        if let Some(ptr) = self.sym_store.0.get(&symbol_name) {
-            Ptr(tag, RawPtr::new(ptr.to_usize()))
-        } else {
-            let ptr = self.sym_store.0.get(symbol_name).unwrap();
-            Ptr(tag, RawPtr::new(ptr.to_usize()))
-        }
+        let idx = self.sym_index(symbol_name)?;
+        Some(Ptr(tag, RawPtr::new(idx)))
     }
 
     fn intern_sym_by_full_name<T: AsRef<str>>(&mut self, name: T) -> Ptr<F> {
         let name = name.as_ref();
+        let sym = Sym::new_absolute(name.into());
+        self.intern_sym_with_path(name, sym.path().iter().map(String::as_str))
+    }
+
+    /// Intern the symbol with full name `name`, whose path segments are `path`.
+    fn intern_sym_with_path<'a>(
+        &mut self,
+        name: &str,
+        path: impl IntoIterator<Item = &'a str>,
+    ) -> Ptr<F> {
         self.hash_string_mut(name);
 
-        let (tag, symbol_name) = if name == ".LURK.NIL" {
+        let (tag, symbol_name) = if name == ".LURK.NIL" && !self.nil_as_symbol {
             (ExprTag::Nil, "LURK.NIL")
         } else {
             let (names_keyword, symbol_name) = names_keyword(name);
@@ -1439,58 +2900,66 @@ impl<F: LurkField> Store<F> {
 
         // We need to intern each of the path segments individually, so they will be in the store.
         // Otherwise, there can be an error when calling `hash_symbol()` with an immutable store.
-        Sym::new_absolute(name.into()).path().iter().for_each(|x| {
-            self.intern_str(x);
-        });
+        for segment in path {
+            self.intern_str(segment);
+        }
 
-        if let Some(ptr) = self.sym_store.0.get(&symbol_name) {
-            Ptr(tag, RawPtr::new(ptr.to_usize()))
+        if let Some(idx) = self.sym_index(symbol_name) {
+            Ptr(tag, RawPtr::new(idx))
         } else {
-            let ptr = self.sym_store.0.get_or_intern(symbol_name);
-            let ptr = Ptr(tag, RawPtr::new(ptr.to_usize()));
+            let ptr = Ptr(tag, RawPtr::new(self.intern_new_sym_name(symbol_name)));
             self.dehydrated.push(ptr);
             ptr
         }
     }
 
+    /// Intern `name`, which `sym_index` does not resolve, as a new symbol and return its index. If the interner still
+    /// holds `name` for a symbol `rename_sym` renamed, the new symbol is interned under an unused key instead and
+    /// renamed to `name`.
+    fn intern_new_sym_name(&mut self, name: &str) -> usize {
+        if self.sym_store.0.get(name).is_none() {
+            return self.sym_store.0.get_or_intern(name).to_usize();
+        }
+        let key = (0..)
+            .map(|n| format!("{name}\0{n}"))
+            .find(|key| self.sym_store.0.get(key).is_none())
+            .expect("unused interner key");
+        let idx = self.sym_store.0.get_or_intern(key).to_usize();
+        self.sym_renames.insert(idx, name.into());
+        self.sym_rename_index.insert(name.into(), idx);
+        idx
+    }
+
     pub fn get_lurk_sym<T: AsRef<str>>(&self, name: T, convert_case: bool) -> Option<Ptr<F>> {
         let mut name = format!(".lurk.{}", name.as_ref());
         if convert_case {
-            crate::parser::convert_sym_case(&mut name);
+            crate::parser::convert_sym_case(&mut name, CaseMode::Upper);
         }
 
         Some(self.get_sym_by_full_name(name))
     }
 
+    /// Intern `num` by its canonical representation, so numerically equal values share one entry however they
+    /// were constructed.
     pub fn intern_num<T: Into<Num<F>>>(&mut self, num: T) -> Ptr<F> {
-        let num = num.into();
-        let num = match num {
-            Num::Scalar(scalar) => {
-                if let Some(u64_num) = scalar.to_u64() {
-                    Num::U64(u64_num)
-                } else {
-                    num
-                }
-            }
-            Num::U64(_) => num,
-        };
+        let num = num.into().canonical();
         let (ptr, _) = self.num_store.insert_full(num);
 
         Ptr(ExprTag::Num, RawPtr::new(ptr))
     }
 
+    /// Intern the num whose field element has the canonical little-endian representation `bytes`, without going
+    /// through `u64`. Rejects input of the wrong length or not below the modulus.
+    pub fn intern_num_from_bytes(&mut self, bytes: &[u8]) -> Result<Ptr<F>, StoreError> {
+        if bytes.len() != F::default().to_repr().as_ref().len() {
+            return Err(StoreError::NonCanonicalBytes);
+        }
+        let f = F::from_bytes(bytes).ok_or(StoreError::NonCanonicalBytes)?;
+        Ok(self.intern_num(Num::Scalar(f)))
+    }
+
     pub fn get_num<T: Into<Num<F>>>(&self, num: T) -> Option<Ptr<F>> {
-        let num = num.into();
-        let num = match num {
-            Num::Scalar(scalar) => {
-                if let Some(u64_num) = scalar.to_u64() {
-                    Num::U64(u64_num)
-                } else {
-                    num
-                }
-            }
-            Num::U64(_) => num,
-        };
+        let num = num.into().canonical();
 
         self.num_store
             .get_index_of::<Num<F>>(&num)
@@ -1509,12 +2978,29 @@ impl<F: LurkField> Store<F> {
         Ptr(ExprTag::U64, RawPtr::new(n as usize))
     }
 
+    /// Returns the `U64` pointer for `num`, or `StoreError::OutOfRange` if its value does not round-trip through
+    /// `u64`, rather than silently truncating it.
+    pub fn try_intern_u64_checked<T: Into<Num<F>>>(
+        &mut self,
+        num: T,
+    ) -> Result<Ptr<F>, StoreError> {
+        match num.into().canonical() {
+            Num::U64(n) => Ok(self.get_u64(n)),
+            num => Err(StoreError::OutOfRange(num.to_string())),
+        }
+    }
+
     pub fn intern_str<T: AsRef<str>>(&mut self, str: T) -> Ptr<F> {
         // Hash string for side effect. This will cause all tails to be interned.
         self.hash_string_mut(str.as_ref());
         self.intern_str_aux(str)
     }
 
+    /// Intern the string made of `chars`, as by `intern_str`.
+    pub fn intern_str_from_chars<I: Iterator<Item = char>>(&mut self, chars: I) -> Ptr<F> {
+        self.intern_str(chars.collect::<String>())
+    }
+
     fn intern_str_aux<T: AsRef<str>>(&mut self, str: T) -> Ptr<F> {
         if let Some(ptr) = self.str_store.0.get(&str) {
             Ptr(ExprTag::Str, RawPtr::new(ptr.to_usize()))
@@ -1534,8 +3020,32 @@ impl<F: LurkField> Store<F> {
 
     pub fn get_sym<T: AsRef<str>>(&self, sym: Sym) -> Option<Ptr<F>> {
         let name = sym.full_sym_name();
-        let ptr = self.sym_store.0.get(name)?;
-        Some(Ptr(ExprTag::Sym, RawPtr::new(ptr.to_usize())))
+        let idx = self.sym_index(&name)?;
+        Some(Ptr(ExprTag::Sym, RawPtr::new(idx)))
+    }
+
+    /// Whether the symbol with interner name `name` (as returned by `sym_name_cow`) exists, either as given, which
+    /// finds symbols read under `CaseMode::Preserve`, or after uppercasing as by `sym`. Unlike `get_sym`, this does
+    /// not allocate unless `name` is both long and not already uppercase.
+    pub fn contains_sym(&self, name: &str) -> bool {
+        const BUF_LEN: usize = 64;
+
+        if self.sym_index(name).is_some() {
+            return true;
+        }
+        if !name.bytes().any(|b| b.is_ascii_lowercase()) {
+            return false;
+        }
+        if name.len() > BUF_LEN {
+            return self.sym_index(&name.to_ascii_uppercase()).is_some();
+        }
+        let mut buf = [0u8; BUF_LEN];
+        let buf = &mut buf[..name.len()];
+        buf.copy_from_slice(name.as_bytes());
+        buf.make_ascii_uppercase();
+        // ASCII case conversion leaves multi-byte sequences untouched, so the buffer is still valid UTF-8.
+        let upper = std::str::from_utf8(buf).expect("uppercasing preserves UTF-8");
+        self.sym_index(upper).is_some()
     }
 
     pub fn intern_fun(&mut self, arg: Ptr<F>, body: Ptr<F>, closed_env: Ptr<F>) -> Ptr<F> {
@@ -1549,6 +3059,16 @@ impl<F: LurkField> Store<F> {
         ptr
     }
 
+    /// Interns a fun sharing the `arg` and `body` of the already-interned fun `template`, closed over `env` instead,
+    /// without re-interning the body. Returns `None` if `template` is not a fun or is opaque.
+    pub fn intern_closure(&mut self, template: &Ptr<F>, env: &Ptr<F>) -> Option<Ptr<F>> {
+        if !matches!(template.0, ExprTag::Fun) {
+            return None;
+        }
+        let (arg, body, _) = *self.fetch_fun(template)?;
+        Some(self.intern_fun(arg, body, *env))
+    }
+
     pub fn intern_thunk(&mut self, thunk: Thunk<F>) -> Ptr<F> {
         let (p, inserted) = self.thunk_store.insert_full(thunk);
         let ptr = Ptr(ExprTag::Thunk, RawPtr::new(p));
@@ -1564,18 +3084,18 @@ impl<F: LurkField> Store<F> {
     }
 
     pub fn get_cont_outermost(&self) -> ContPtr<F> {
-        Continuation::Outermost.get_simple_cont()
+        ContPtr::OUTERMOST
     }
     pub fn get_cont_error(&self) -> ContPtr<F> {
-        Continuation::Error.get_simple_cont()
+        ContPtr::ERROR
     }
 
     pub fn get_cont_terminal(&self) -> ContPtr<F> {
-        Continuation::Terminal.get_simple_cont()
+        ContPtr::TERMINAL
     }
 
     pub fn get_cont_dummy(&self) -> ContPtr<F> {
-        Continuation::Dummy.get_simple_cont()
+        ContPtr::DUMMY
     }
 
     pub fn intern_cont_error(&mut self) -> ContPtr<F> {
@@ -1594,30 +3114,324 @@ impl<F: LurkField> Store<F> {
         self.mark_dehydrated_cont(self.get_cont_dummy())
     }
 
-    pub fn scalar_from_parts(&self, tag: F, value: F) -> Option<ScalarPtr<F>> {
-        let Some(e_tag) = ExprTag::from_field(&tag) else { return None };
-        let scalar_ptr = ScalarPtr::from_parts(e_tag, value);
-        self.scalar_ptr_map
-            .contains_key(&scalar_ptr)
-            .then_some(scalar_ptr)
-    }
-
-    pub fn scalar_from_parts_cont(&self, tag: F, value: F) -> Option<ScalarContPtr<F>> {
-        let Some(e_tag) = ContTag::from_field(&tag) else { return None };
-        let scalar_ptr = ScalarContPtr::from_parts(e_tag, value);
-        if self.scalar_ptr_cont_map.contains_key(&scalar_ptr) {
-            return Some(scalar_ptr);
-        }
-        None
+    /// Intern `cont` after checking that each of its fields is a non-dangling pointer of the expected kind: envs and
+    /// argument lists are nil or conses, bound variables are symbols, called functions are funs, and continuations
+    /// exist. Unlike unchecked interning, a mistyped field fails with `StoreError::InvalidContinuationField`
+    /// instead of silently producing a wrong hash.
+    pub fn try_intern_cont(&mut self, cont: Continuation<F>) -> Result<ContPtr<F>, StoreError> {
+        self.validate_continuation(&cont)?;
+        Ok(cont.intern_aux(self))
     }
 
-    pub fn fetch_scalar(&self, scalar_ptr: &ScalarPtr<F>) -> Option<Ptr<F>> {
-        self.scalar_ptr_map.get(scalar_ptr).map(|p| *p)
-    }
+    /// Checked `Continuation::Binop`. See `try_intern_cont`.
+    pub fn try_intern_cont_binop(
+        &mut self,
+        operator: Op2,
+        saved_env: Ptr<F>,
+        unevaled_args: Ptr<F>,
+        continuation: ContPtr<F>,
+    ) -> Result<ContPtr<F>, StoreError> {
+        self.try_intern_cont(Continuation::Binop {
+            operator,
+            saved_env,
+            unevaled_args,
+            continuation,
+        })
+    }
+
+    fn validate_continuation(&self, cont: &Continuation<F>) -> Result<(), StoreError> {
+        let invalid = |field: &'static str, tag: String, expected: &'static str| {
+            StoreError::InvalidContinuationField {
+                cont: cont.cont_tag().to_string(),
+                field,
+                tag,
+                expected,
+            }
+        };
+        let expr = |field: &'static str, ptr: &Ptr<F>, expected: FieldKind| {
+            if self.is_dangling(ptr) {
+                Err(invalid(
+                    field,
+                    format!("dangling {}", ptr.0),
+                    expected.name(),
+                ))
+            } else if !expected.accepts(ptr.0) {
+                Err(invalid(field, ptr.0.to_string(), expected.name()))
+            } else {
+                Ok(())
+            }
+        };
+        let cont_field = |ptr: &ContPtr<F>| {
+            if self.is_dangling_cont(ptr) {
+                Err(invalid(
+                    "continuation",
+                    format!("dangling {}", ptr.0),
+                    "continuation",
+                ))
+            } else {
+                Ok(())
+            }
+        };
 
-    pub fn fetch_scalar_cont(&self, scalar_ptr: &ScalarContPtr<F>) -> Option<ContPtr<F>> {
-        self.scalar_ptr_cont_map.get(scalar_ptr).map(|p| *p)
-    }
+        match cont {
+            Continuation::Outermost
+            | Continuation::Error
+            | Continuation::Dummy
+            | Continuation::Terminal => Ok(()),
+            Continuation::Call0 {
+                saved_env,
+                continuation,
+            }
+            | Continuation::Tail {
+                saved_env,
+                continuation,
+            }
+            | Continuation::Lookup {
+                saved_env,
+                continuation,
+            } => {
+                expr("saved_env", saved_env, FieldKind::Env)?;
+                cont_field(continuation)
+            }
+            Continuation::Call {
+                unevaled_arg,
+                saved_env,
+                continuation,
+            } => {
+                expr("unevaled_arg", unevaled_arg, FieldKind::Expression)?;
+                expr("saved_env", saved_env, FieldKind::Env)?;
+                cont_field(continuation)
+            }
+            Continuation::Call2 {
+                function,
+                saved_env,
+                continuation,
+            } => {
+                expr("function", function, FieldKind::Fun)?;
+                expr("saved_env", saved_env, FieldKind::Env)?;
+                cont_field(continuation)
+            }
+            Continuation::Unop { continuation, .. } | Continuation::Emit { continuation } => {
+                cont_field(continuation)
+            }
+            Continuation::Binop {
+                saved_env,
+                unevaled_args,
+                continuation,
+                ..
+            } => {
+                expr("saved_env", saved_env, FieldKind::Env)?;
+                expr("unevaled_args", unevaled_args, FieldKind::List)?;
+                cont_field(continuation)
+            }
+            Continuation::Binop2 {
+                evaled_arg,
+                continuation,
+                ..
+            } => {
+                expr("evaled_arg", evaled_arg, FieldKind::Expression)?;
+                cont_field(continuation)
+            }
+            Continuation::If {
+                unevaled_args,
+                continuation,
+            } => {
+                expr("unevaled_args", unevaled_args, FieldKind::List)?;
+                cont_field(continuation)
+            }
+            Continuation::Let {
+                var,
+                body,
+                saved_env,
+                continuation,
+            }
+            | Continuation::LetRec {
+                var,
+                saved_env,
+                body,
+                continuation,
+            } => {
+                expr("var", var, FieldKind::Sym)?;
+                expr("body", body, FieldKind::Expression)?;
+                expr("saved_env", saved_env, FieldKind::Env)?;
+                cont_field(continuation)
+            }
+        }
+    }
+
+    pub fn scalar_from_parts(&self, tag: F, value: F) -> Option<ScalarPtr<F>> {
+        let Some(e_tag) = ExprTag::from_field(&tag) else { return None };
+        let scalar_ptr = ScalarPtr::from_parts(e_tag, value);
+        self.scalar_ptr_map
+            .contains_key(&scalar_ptr)
+            .then_some(scalar_ptr)
+    }
+
+    pub fn scalar_from_parts_cont(&self, tag: F, value: F) -> Option<ScalarContPtr<F>> {
+        let Some(e_tag) = ContTag::from_field(&tag) else { return None };
+        let scalar_ptr = ScalarContPtr::from_parts(e_tag, value);
+        if self.scalar_ptr_cont_map.contains_key(&scalar_ptr) {
+            return Some(scalar_ptr);
+        }
+        None
+    }
+
+    /// Decode the tag of `scalar` from its field representation, as when ingesting raw scalar data. This recovers
+    /// only the type; `fetch_scalar` finds the corresponding `Ptr` if the store already knows the value, otherwise
+    /// the tag determines which interning path (e.g. `intern_scalar_ptr`) must reconstruct it.
+    pub fn tag_from_scalar(&self, scalar: &ScalarPtr<F>) -> Option<ExprTag> {
+        ExprTag::from_field(&scalar.tag_field())
+    }
+
+    pub fn fetch_scalar(&self, scalar_ptr: &ScalarPtr<F>) -> Option<Ptr<F>> {
+        self.scalar_ptr_map.get(scalar_ptr).map(|p| *p)
+    }
+
+    pub fn fetch_scalar_cont(&self, scalar_ptr: &ScalarContPtr<F>) -> Option<ContPtr<F>> {
+        self.scalar_ptr_cont_map.get(scalar_ptr).map(|p| *p)
+    }
+
+    /// Sort `ptrs` deterministically. Pointers are ordered first by tag discriminant, so that e.g. all syms precede
+    /// all nums, which precede all strs. Within a tag, syms and strs compare as strings, nums as unsigned integers in
+    /// `[0, p)`, and chars and u64s by value. Other tags have no natural order and compare by store index.
+    pub fn sort_ptrs(&self, ptrs: &mut [Ptr<F>]) {
+        ptrs.sort_by(|a, b| self.cmp_ptrs(a, b));
+    }
+
+    fn cmp_ptrs(&self, a: &Ptr<F>, b: &Ptr<F>) -> core::cmp::Ordering {
+        u16::from(a.tag())
+            .cmp(&u16::from(b.tag()))
+            .then_with(|| match a.tag() {
+                ExprTag::Sym | ExprTag::Key => self
+                    .fetch_sym(a)
+                    .map(|s| s.full_name())
+                    .cmp(&self.fetch_sym(b).map(|s| s.full_name())),
+                ExprTag::Str => self.fetch_str(a).cmp(&self.fetch_str(b)),
+                ExprTag::Num => match (self.fetch_num(a), self.fetch_num(b)) {
+                    (Some(a), Some(b)) => a.cmp_as_integer(b),
+                    (a, b) => a.is_some().cmp(&b.is_some()),
+                },
+                _ => a.1.idx().cmp(&b.1.idx()),
+            })
+    }
+
+    /// All interned symbol names, in index order. Symbols and keywords with the same name share an entry, so names
+    /// are given without their leading marker.
+    pub fn export_symbols(&self) -> Vec<String> {
+        self.sym_store
+            .0
+            .into_iter()
+            .map(
+                |(symbol, name)| match self.sym_renames.get(&symbol.to_usize()) {
+                    Some(renamed) => renamed.clone(),
+                    None => name.to_string(),
+                },
+            )
+            .collect()
+    }
+
+    /// Pointers to all symbols whose name starts with `prefix`, after converting its case to the store's case mode,
+    /// in index order. A name matches either as interned (as returned by `sym_name_cow`) or relative to the Lurk
+    /// package, so both `C` and `LURK.C` match `LURK.CAR`. This is a linear scan, meant for completion. Symbols and
+    /// keywords share interner entries, so symbol pointers are returned; nil is returned as nil.
+    pub fn syms_with_prefix(&self, prefix: &str) -> Vec<Ptr<F>> {
+        let mut prefix = prefix.to_string();
+        convert_sym_case(&mut prefix, self.case_mode);
+        let package = format!("{}.", self.lurk_package.name().full_sym_name());
+
+        self.sym_store
+            .0
+            .into_iter()
+            .filter_map(|(symbol, name)| {
+                let idx = symbol.to_usize();
+                let name = self.sym_renames.get(&idx).map_or(name, String::as_str);
+                let relative = name.strip_prefix(&package).unwrap_or(name);
+                if name.is_empty() || !(name.starts_with(&prefix) || relative.starts_with(&prefix))
+                {
+                    return None;
+                }
+                let tag = if name == "LURK.NIL" && !self.nil_as_symbol {
+                    ExprTag::Nil
+                } else {
+                    ExprTag::Sym
+                };
+                Some(Ptr(tag, RawPtr::new(idx)))
+            })
+            .collect()
+    }
+
+    /// Intern the symbol `names` produced by `export_symbols`, in order. If this store's symbols are a prefix of
+    /// `names` (as is the case for two stores which have only interned the well-known symbols), every symbol then
+    /// has the same index, and hence the same `Ptr`, in both stores.
+    pub fn import_symbols(&mut self, names: &[String]) {
+        for name in names {
+            if name.is_empty() {
+                self.intern_sym_by_full_name("");
+            } else {
+                self.intern_sym_by_full_name(format!("{SYM_MARKER}{name}"));
+            }
+        }
+    }
+
+    /// Rename the interned symbol `old` to `new`, both given as interner names (as returned by `sym_name_cow`).
+    ///
+    /// Symbols are interned by identity, so this does not create a new symbol: every existing `Ptr` to `old` keeps
+    /// its index, which now resolves to `new`, and interning `new` returns those same pointers. `old` no longer names
+    /// any symbol, so interning it afterwards creates a new one. Since any structure may reach the renamed symbol, all
+    /// cached hashes of non-opaque expressions are dropped and their pointers marked dehydrated, to be rehashed on
+    /// demand or by `hydrate_scalar_cache`. The reverse map of continuations, whose hashes may also reach it, is
+    /// cleared and all continuations marked dehydrated.
+    pub fn rename_sym(&mut self, old: &str, new: &str) -> Result<(), StoreError> {
+        let idx = self
+            .sym_index(old)
+            .ok_or_else(|| StoreError::UnknownSymbol(old.into()))?;
+        if self.sym_index(new).is_some() {
+            return Err(StoreError::SymbolExists(new.into()));
+        }
+
+        // As in `intern_sym_by_full_name`, the path segments must be interned for `hash_symbol`, which also looks up
+        // each proper prefix of the path as a symbol.
+        let path = Sym::new_absolute(format!("{SYM_MARKER}{new}"))
+            .path()
+            .clone();
+        for segment in &path {
+            self.intern_str(segment);
+        }
+        for i in 1..path.len() {
+            self.intern_sym(&Sym::new_from_path(false, path[..i].to_vec()));
+        }
+        if let Some(previous) = self.sym_renames.insert(idx, new.into()) {
+            self.sym_rename_index.remove(&previous);
+        }
+        self.sym_rename_index.insert(new.into(), idx);
+
+        let stale: Vec<Ptr<F>> = self
+            .pointer_scalar_ptr_cache
+            .iter()
+            .map(|entry| *entry.key())
+            .filter(|ptr| !ptr.is_opaque())
+            .collect();
+        self.pointer_scalar_ptr_cache
+            .retain(|ptr, _| ptr.is_opaque());
+        self.scalar_ptr_map.retain(|_, ptr| ptr.is_opaque());
+        self.dehydrated.extend(stale);
+        self.scalar_ptr_cont_map.clear();
+        let conts: Vec<ContPtr<F>> = self.cont_ptrs().collect();
+        self.dehydrated_cont.extend(conts);
+        self.constants.take();
+
+        Ok(())
+    }
+
+    /// The index in `sym_store` of the symbol with interner name `name`, taking renames into account.
+    fn sym_index(&self, name: &str) -> Option<usize> {
+        if let Some(&idx) = self.sym_rename_index.get(name) {
+            return Some(idx);
+        }
+        // The interner still holds the former names of renamed symbols, which no longer resolve.
+        let idx = self.sym_store.0.get(name)?.to_usize();
+        (!self.sym_renames.contains_key(&idx)).then_some(idx)
+    }
 
     pub fn fetch_sym(&self, ptr: &Ptr<F>) -> Option<Sym> {
         debug_assert!(matches!(ptr.0, ExprTag::Sym | ExprTag::Key | ExprTag::Nil));
@@ -1631,14 +3445,31 @@ impl<F: LurkField> Store<F> {
         if ptr.0 == ExprTag::Nil {
             return Some(Sym::new(".LURK.NIL".into()));
         };
+        self.sym_name_cow(ptr).map(|s| match ptr.0 {
+            ExprTag::Sym => Sym::new_sym(s.into()),
+            ExprTag::Key => Sym::new_key(s.into()),
+            _ => unreachable!(),
+        })
+    }
+
+    /// The interned name of the symbol, keyword or nil `ptr`, without its leading marker. Names are borrowed from the
+    /// interner, except for nil, whose name is static. Opaque symbols have no name.
+    pub fn sym_name_cow(&self, ptr: &Ptr<F>) -> Option<Cow<'_, str>> {
+        debug_assert!(matches!(ptr.0, ExprTag::Sym | ExprTag::Key | ExprTag::Nil));
+
+        if ptr.1.is_opaque() {
+            return None;
+        }
+        if ptr.0 == ExprTag::Nil {
+            return Some(Cow::Borrowed("LURK.NIL"));
+        }
+        if let Some(name) = self.sym_renames.get(&ptr.1.idx()) {
+            return Some(Cow::Borrowed(name));
+        }
         self.sym_store
             .0
-            .resolve(SymbolUsize::try_from_usize(ptr.1.idx()).unwrap())
-            .map(|s| match ptr.0 {
-                ExprTag::Sym => Sym::new_sym(s.into()),
-                ExprTag::Key => Sym::new_key(s.into()),
-                _ => unreachable!(),
-            })
+            .resolve(SymbolUsize::try_from_usize(ptr.1.idx())?)
+            .map(Cow::Borrowed)
     }
 
     pub fn fetch_str(&self, ptr: &Ptr<F>) -> Option<&str> {
@@ -1685,6 +3516,32 @@ impl<F: LurkField> Store<F> {
         self.num_store.get_index(ptr.1.idx())
     }
 
+    /// The cons at insertion index `i` of `cons_store`, i.e. the `i`-th distinct cons interned.
+    pub fn cons_at_index(&self, i: usize) -> Option<(Ptr<F>, Ptr<F>)> {
+        self.cons_store.get_index(i).copied()
+    }
+
+    /// All conses as `(index, (car, cdr))`, in insertion order. Unlike iterating the hash-keyed caches, this order
+    /// is reproducible and suitable for dumps.
+    pub fn cons_entries(&self) -> impl Iterator<Item = (usize, &(Ptr<F>, Ptr<F>))> {
+        self.cons_store.iter().enumerate()
+    }
+
+    /// All commitments as `(index, (secret, payload))`, in insertion order.
+    pub fn comm_entries(&self) -> impl Iterator<Item = (usize, &(FWrap<F>, Ptr<F>))> {
+        self.comm_store.iter().enumerate()
+    }
+
+    /// All functions as `(index, (arg, body, closed_env))`, in insertion order.
+    pub fn fun_entries(&self) -> impl Iterator<Item = (usize, &(Ptr<F>, Ptr<F>, Ptr<F>))> {
+        self.fun_store.iter().enumerate()
+    }
+
+    /// All numbers as `(index, num)`, in insertion order.
+    pub fn num_entries(&self) -> impl Iterator<Item = (usize, &Num<F>)> {
+        self.num_store.iter().enumerate()
+    }
+
     fn fetch_thunk(&self, ptr: &Ptr<F>) -> Option<&Thunk<F>> {
         debug_assert!(matches!(ptr.0, ExprTag::Thunk));
         self.thunk_store.get_index(ptr.1.idx())
@@ -1720,6 +3577,102 @@ impl<F: LurkField> Store<F> {
         }
     }
 
+    /// A tagged JSON tree for the expression at `ptr`: `{"cons":[car,cdr]}`, `{"num":"0x..."}`, `{"sym":".FOO"}`,
+    /// `{"str":"..."}`, `{"char":"a"}`, `{"u64":"1"}`, `{"fun":[arg,body,env]}` or `{"comm":["0x...",payload]}`. Field
+    /// elements are big-endian hex strings and symbols are full names, so `from_json` can re-intern them. Shared
+    /// subexpressions are duplicated. Thunks and opaque pointers have no representation and become `null`.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self, ptr: &Ptr<F>) -> serde_json::Value {
+        use serde_json::{json, Value};
+
+        let hex = |f: F| format!("0x{}", f.hex_digits());
+        match self.fetch(ptr) {
+            Some(Expression::Nil | Expression::Sym(_)) => self
+                .fetch_sym(ptr)
+                .map_or(Value::Null, |sym| json!({ "sym": sym.full_name() })),
+            Some(Expression::Cons(car, cdr)) => {
+                json!({ "cons": [self.to_json(&car), self.to_json(&cdr)] })
+            }
+            Some(Expression::Comm(secret, payload)) => {
+                json!({ "comm": [hex(secret), self.to_json(&payload)] })
+            }
+            Some(Expression::Fun(arg, body, closed_env)) => json!({
+                "fun": [self.to_json(&arg), self.to_json(&body), self.to_json(&closed_env)]
+            }),
+            Some(Expression::Num(num)) => json!({ "num": hex(num.into_scalar()) }),
+            Some(Expression::Str(str)) => json!({ "str": str }),
+            Some(Expression::Char(c)) => json!({ "char": c.to_string() }),
+            Some(Expression::UInt(UInt::U64(n))) => json!({ "u64": n.to_string() }),
+            Some(Expression::Thunk(_) | Expression::Opaque(_)) | None => Value::Null,
+        }
+    }
+
+    /// Interns the expression described by `v`, in the format produced by `to_json`.
+    #[cfg(feature = "json")]
+    pub fn from_json(&mut self, v: &serde_json::Value) -> Result<Ptr<F>, StoreError> {
+        let invalid = || StoreError::InvalidJson(v.to_string());
+        let (tag, body) = match v.as_object() {
+            Some(obj) if obj.len() == 1 => obj.iter().next().unwrap(),
+            _ => return Err(invalid()),
+        };
+        let str_body = || body.as_str().ok_or_else(invalid);
+        let children = |n: usize| match body.as_array() {
+            Some(children) if children.len() == n => Ok(children),
+            _ => Err(invalid()),
+        };
+        let field = |hex: &str| {
+            hex.strip_prefix("0x")
+                .and_then(field_from_hex::<F>)
+                .ok_or_else(invalid)
+        };
+
+        match tag.as_str() {
+            "cons" => {
+                let children = children(2)?;
+                let car = self.from_json(&children[0])?;
+                let cdr = self.from_json(&children[1])?;
+                Ok(self.intern_cons(car, cdr))
+            }
+            "comm" => {
+                let children = children(2)?;
+                let secret = field(children[0].as_str().ok_or_else(invalid)?)?;
+                let payload = self.from_json(&children[1])?;
+                Ok(self.intern_comm(secret, payload))
+            }
+            "fun" => {
+                let children = children(3)?;
+                let arg = self.from_json(&children[0])?;
+                let body = self.from_json(&children[1])?;
+                let closed_env = self.from_json(&children[2])?;
+                if !matches!(arg.0, ExprTag::Sym) {
+                    return Err(invalid());
+                }
+                Ok(self.intern_fun(arg, body, closed_env))
+            }
+            "num" => Ok(self.intern_num(Num::Scalar(field(str_body()?)?))),
+            "sym" => {
+                let name = str_body()?;
+                if !name.starts_with(['.', ':']) {
+                    return Err(invalid());
+                }
+                Ok(self.intern_sym_by_full_name(name))
+            }
+            "str" => Ok(self.intern_str(str_body()?)),
+            "char" => {
+                let mut chars = str_body()?.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(self.get_char(c)),
+                    _ => Err(invalid()),
+                }
+            }
+            "u64" => str_body()?
+                .parse::<u64>()
+                .map(|n| self.get_u64(n))
+                .map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+
     pub fn fetch_cont(&self, ptr: &ContPtr<F>) -> Option<Continuation<F>> {
         use ContTag::*;
         match ptr.0 {
@@ -1824,6 +3777,20 @@ impl<F: LurkField> Store<F> {
         }
     }
 
+    /// Lisp-style `car` and `cdr` of the string `ptr`: its first char and the rest of the string. As with `car_cdr`,
+    /// the empty string yields nil and the empty string. Returns `None` if `ptr` is not an interned string, or if the
+    /// rest of the string is not interned (which interning the string ensures).
+    pub fn string_car_cdr(&self, ptr: &Ptr<F>) -> Option<(Ptr<F>, Ptr<F>)> {
+        if ptr.tag() != ExprTag::Str || ptr.is_opaque() {
+            return None;
+        }
+        let s = self.fetch_str(ptr)?;
+        match s.chars().next() {
+            Some(c) => Some((self.get_char(c), self.get_str(&s[c.len_utf8()..])?)),
+            None => Some((self.get_nil(), *ptr)),
+        }
+    }
+
     /// Mutable version of car_cdr to handle Str. `(cdr str)` may return a new str (the tail), which must be allocated.
     pub fn car_cdr_mut(&mut self, ptr: &Ptr<F>) -> Result<(Ptr<F>, Ptr<F>), Error> {
         match ptr.0 {
@@ -1879,10 +3846,251 @@ impl<F: LurkField> Store<F> {
         }
     }
 
+    /// Follow `path` from `ptr`, taking the car or cdr at each step. Unlike `car_cdr`, every step must be a
+    /// non-opaque cons: `None` is returned otherwise, including for nil and strings.
+    pub fn nth_car(&self, ptr: &Ptr<F>, path: &[CarCdr]) -> Option<Ptr<F>> {
+        path.iter().try_fold(*ptr, |ptr, step| {
+            if ptr.tag() != ExprTag::Cons || ptr.is_opaque() {
+                return None;
+            }
+            let (car, cdr) = self.fetch_cons(&ptr)?;
+            match step {
+                CarCdr::Car => Some(*car),
+                CarCdr::Cdr => Some(*cdr),
+            }
+        })
+    }
+
+    /// `(car (cdr ptr))`, as by `nth_car`.
+    pub fn cadr(&self, ptr: &Ptr<F>) -> Option<Ptr<F>> {
+        self.nth_car(ptr, &[CarCdr::Cdr, CarCdr::Car])
+    }
+
+    /// `(car (cdr (cdr ptr)))`, as by `nth_car`.
+    pub fn caddr(&self, ptr: &Ptr<F>) -> Option<Ptr<F>> {
+        self.nth_car(ptr, &[CarCdr::Cdr, CarCdr::Cdr, CarCdr::Car])
+    }
+
+    /// The maximum cons/fun nesting depth of `ptr`: atoms (including nil) have depth 0, and a cons or fun is one
+    /// deeper than its deepest child. So `(1 2 3)` has depth 3 and `((1 . 2) . (3 . 4))` depth 2. The traversal is
+    /// iterative, so arbitrarily deep structures are fine.
+    pub fn expr_depth(&self, ptr: &Ptr<F>) -> usize {
+        let mut depths: HashMap<Ptr<F>, usize> = HashMap::new();
+        let mut stack = vec![(*ptr, false)];
+
+        while let Some((ptr, children_done)) = stack.pop() {
+            if depths.contains_key(&ptr) {
+                continue;
+            }
+            let children = self.nesting_children(&ptr);
+            if children.is_empty() {
+                depths.insert(ptr, 0);
+            } else if children_done {
+                let deepest = children
+                    .iter()
+                    .map(|child| depths[child])
+                    .max()
+                    .unwrap_or(0);
+                depths.insert(ptr, deepest + 1);
+            } else {
+                stack.push((ptr, true));
+                stack.extend(children.into_iter().map(|child| (child, false)));
+            }
+        }
+        depths[ptr]
+    }
+
+    /// The number of distinct pointers reachable from `ptr`, including itself, so shared subexpressions count once.
+    /// Conses, funs and the payloads of comms are followed; opaque pointers are leaves.
+    pub fn node_count(&self, ptr: &Ptr<F>) -> usize {
+        let mut seen = HashSet::new();
+        let mut stack = vec![*ptr];
+
+        while let Some(ptr) = stack.pop() {
+            if seen.insert(ptr) {
+                stack.extend(self.nesting_children(&ptr));
+                if ptr.tag() == ExprTag::Comm {
+                    if let Some((_, payload)) = self.fetch_comm(&ptr) {
+                        stack.push(*payload);
+                    }
+                }
+            }
+        }
+        seen.len()
+    }
+
+    /// The `ScalarPtr` of every expression reachable from `root`, each listed once and after all of its children, so
+    /// the last one is `root`'s. Children are the components of conses, funs and comms and the values of thunks.
+    pub fn reachable_scalar_ptrs_topo(&self, root: &Ptr<F>) -> Vec<ScalarPtr<F>> {
+        let mut seen = HashSet::new();
+        let mut order = Vec::new();
+        let mut stack = vec![(*root, false)];
+
+        while let Some((ptr, children_done)) = stack.pop() {
+            if children_done {
+                order.extend(self.hash_expr(&ptr));
+            } else if seen.insert(ptr) {
+                stack.push((ptr, true));
+                let mut children = self.nesting_children(&ptr);
+                match ptr.tag() {
+                    ExprTag::Comm => children.extend(self.fetch_comm(&ptr).map(|(_, p)| *p)),
+                    ExprTag::Thunk if !ptr.is_opaque() => {
+                        children.extend(self.fetch_thunk(&ptr).map(|thunk| thunk.value))
+                    }
+                    _ => (),
+                }
+                // Reversed, so children are visited left to right.
+                stack.extend(children.into_iter().rev().map(|child| (child, false)));
+            }
+        }
+        order
+    }
+
+    /// Like `hash_expr`, but on failure reports which descendants of `ptr`, possibly `ptr` itself, are dangling, i.e.
+    /// not yet interned. The subexpressions that could be hashed are cached as usual. Descendants are found as by
+    /// `reachable_scalar_ptrs_topo`, so the list is empty if hashing failed for another reason.
+    pub fn hash_expr_partial(&self, ptr: &Ptr<F>) -> PartialHash<F> {
+        if let Some(scalar_ptr) = self.hash_expr(ptr) {
+            return PartialHash::Full(scalar_ptr);
+        }
+
+        let mut seen = HashSet::new();
+        let mut missing = Vec::new();
+        let mut stack = vec![*ptr];
+        while let Some(ptr) = stack.pop() {
+            if !seen.insert(ptr) {
+                continue;
+            }
+            if self.is_dangling(&ptr) {
+                missing.push(ptr);
+                continue;
+            }
+            let mut children = self.nesting_children(&ptr);
+            match ptr.tag() {
+                ExprTag::Comm => children.extend(self.fetch_comm(&ptr).map(|(_, p)| *p)),
+                ExprTag::Thunk if !ptr.is_opaque() => {
+                    children.extend(self.fetch_thunk(&ptr).map(|thunk| thunk.value))
+                }
+                _ => (),
+            }
+            stack.extend(children.into_iter().rev());
+        }
+        PartialHash::Missing(missing)
+    }
+
+    /// Every continuation reachable from `root`, in the order first reached: through the continuations of thunks, the
+    /// continuations those continue with, and the expressions any of them reference. Simple continuations such as
+    /// `Outermost` are included.
+    pub fn reachable_conts(&self, root: &Ptr<F>) -> Vec<ContPtr<F>> {
+        let mut seen_exprs = HashSet::new();
+        let mut seen_conts = HashSet::new();
+        let mut conts = Vec::new();
+        let mut pending_exprs = vec![*root];
+        let mut pending_conts = Vec::new();
+        loop {
+            if let Some(ptr) = pending_exprs.pop() {
+                if !seen_exprs.insert(ptr) {
+                    continue;
+                }
+                if let Some(Expression::Thunk(thunk)) = self.fetch(&ptr) {
+                    pending_conts.push(thunk.continuation);
+                }
+                if let Some((_, children)) = self.expr_children(&ptr) {
+                    pending_exprs.extend(children.into_iter().rev());
+                }
+            } else if let Some(cont) = pending_conts.pop() {
+                if !seen_conts.insert(cont) {
+                    continue;
+                }
+                conts.push(cont);
+                if let Some(continuation) = self.fetch_cont(&cont) {
+                    let (exprs, next) = continuation.children();
+                    pending_conts.extend(next);
+                    pending_exprs.extend(exprs.into_iter().rev());
+                }
+            } else {
+                return conts;
+            }
+        }
+    }
+
+    /// The children of a non-opaque cons or fun. Everything else has none.
+    fn nesting_children(&self, ptr: &Ptr<F>) -> Vec<Ptr<F>> {
+        match ptr.tag() {
+            ExprTag::Cons => self
+                .fetch_cons(ptr)
+                .map(|(car, cdr)| vec![*car, *cdr])
+                .unwrap_or_default(),
+            ExprTag::Fun => self
+                .fetch_fun(ptr)
+                .map(|(arg, body, closed_env)| vec![*arg, *body, *closed_env])
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The tag of `ptr` as a field element, with the expressions it directly references: `[car, cdr]` for a cons,
+    /// `[arg, body, closed_env]` for a fun, `[payload]` for a commitment and `[value]` for a thunk, whose continuation
+    /// is not an expression. Atoms and opaque pointers have no children. Returns `None` if `ptr` is not in the store.
+    pub fn expr_children(&self, ptr: &Ptr<F>) -> Option<(F, Vec<Ptr<F>>)> {
+        let children = match self.fetch(ptr)? {
+            Expression::Cons(car, cdr) => vec![car, cdr],
+            Expression::Fun(arg, body, closed_env) => vec![arg, body, closed_env],
+            Expression::Comm(_, payload) => vec![payload],
+            Expression::Thunk(thunk) => vec![thunk.value],
+            Expression::Nil
+            | Expression::Sym(_)
+            | Expression::Num(_)
+            | Expression::Str(_)
+            | Expression::Opaque(_)
+            | Expression::Char(_)
+            | Expression::UInt(_) => Vec::new(),
+        };
+        Some((ptr.tag().to_field(), children))
+    }
+
+    /// Extend `env` with a binding of `var` to `val`, producing `((var . val) . env)` as the evaluator does.
+    pub fn env_extend(&mut self, env: &Ptr<F>, var: &Ptr<F>, val: &Ptr<F>) -> Ptr<F> {
+        let binding = self.cons(*var, *val);
+        self.cons(binding, *env)
+    }
+
+    /// The innermost value bound to `var` in `env`, or `None` if it is unbound. Only simple `(var . val)` bindings
+    /// are considered, and scanning stops at anything that is not a well-formed environment.
+    pub fn env_lookup(&self, env: &Ptr<F>, var: &Ptr<F>) -> Option<Ptr<F>> {
+        let mut env = *env;
+        while env.is_cons() {
+            let (binding, rest) = self.car_cdr(&env).ok()?;
+            if binding.is_cons() {
+                let (v, val) = self.car_cdr(&binding).ok()?;
+                if v == *var {
+                    return Some(val);
+                }
+            }
+            env = rest;
+        }
+        None
+    }
+
     pub fn hash_expr(&self, ptr: &Ptr<F>) -> Option<ScalarPtr<F>> {
         self.hash_expr_aux(ptr, HashScalar::Create)
     }
 
+    /// Whether `ptr` hashes to `expected`. A tag mismatch is detected without hashing.
+    pub fn ptr_has_hash(&self, ptr: &Ptr<F>, expected: &ScalarPtr<F>) -> bool {
+        ptr.tag() == expected.tag() && self.hash_expr(ptr).as_ref() == Some(expected)
+    }
+
+    /// Panic unless `a` in this store and `b` in `other` hash to the same `ScalarPtr`. On mismatch, the message
+    /// shows both `ScalarPtr`s with their values in hex. Available to unit tests, and to integration tests through
+    /// the `test-utils` feature.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn assert_same_hash(&self, other: &Store<F>, a: &Ptr<F>, b: &Ptr<F>) {
+        let left = self.hash_expr(a).expect("left pointer cannot be hashed");
+        let right = other.hash_expr(b).expect("right pointer cannot be hashed");
+        assert!(left == right, "hashes differ: left {left} != right {right}");
+    }
+
     // Get hash for expr, but only if it already exists. This should never cause create_scalar_ptr to be called. Use
     // this after the cache has been hydrated. NOTE: because dashmap::entry can deadlock, it is important not to call
     // hash_expr in nested call graphs which might trigger that behavior. This discovery is what led to get_expr_hash
@@ -1932,6 +4140,32 @@ impl<F: LurkField> Store<F> {
         Some(self.create_cont_scalar_ptr(*ptr, hash))
     }
 
+    /// The `ScalarContPtr` of `cont`, computed without interning it. Its children must be interned.
+    pub fn hash_continuation(&self, cont: &Continuation<F>) -> Option<ScalarContPtr<F>> {
+        let components = self.get_hash_components_continuation(cont)?;
+        let hash = self.poseidon_cache.hash8(&components);
+        Some(ScalarContPtr::from_parts(cont.cont_tag(), hash))
+    }
+
+    /// A digest of the well-known symbols, in `WellKnown::ALL` order: each symbol's `Sym` tag and hash is folded into
+    /// the accumulator with `hash4`, starting from zero. It changes whenever the well-known symbol list or the hashing
+    /// scheme does, so downstream code can pin it to catch incompatible upgrades. Independent of `nil_as_symbol`.
+    pub fn wellknown_digest(&self) -> F {
+        self.wellknown_syms.iter().fold(F::zero(), |acc, &idx| {
+            let sym = self
+                .fetch_sym(&Ptr(ExprTag::Sym, RawPtr::new(idx)))
+                .expect("well-known symbol missing");
+            let hash = self.hash_symbol(&sym, HashScalar::Create);
+            self.poseidon_cache
+                .hash4(&[acc, ExprTag::Sym.to_field(), hash, F::zero()])
+        })
+    }
+
+    /// Hash precomputed continuation components, as returned by `get_hash_components_cont`.
+    pub fn hash_cont_components(&self, components: &[F; 8]) -> F {
+        self.poseidon_cache.hash8(components)
+    }
+
     fn scalar_ptr(&self, ptr: Ptr<F>, hash: F, mode: HashScalar) -> ScalarPtr<F> {
         match mode {
             HashScalar::Create => self.create_scalar_ptr(ptr, hash),
@@ -1944,13 +4178,105 @@ impl<F: LurkField> Store<F> {
     fn create_scalar_ptr(&self, ptr: Ptr<F>, hash: F) -> ScalarPtr<F> {
         let scalar_ptr = ScalarPtr::from_parts(ptr.0, hash);
         let entry = self.scalar_ptr_map.entry(scalar_ptr);
-        entry.or_insert(ptr);
+        let existing = *entry.or_insert(ptr);
+        // An opaque pointer legitimately shares its hash with the expression it stands for. So do copies of an
+        // expression in a store whose conses are not deduplicated, where the reverse map keeps the first copy hashed.
+        debug_assert!(
+            existing == ptr
+                || existing.is_opaque()
+                || ptr.is_opaque()
+                || self.are_copies(existing, ptr),
+            "hash collision: {existing:?} and {ptr:?} both hash to {scalar_ptr}"
+        );
 
         let entry2 = self.pointer_scalar_ptr_cache.entry(ptr);
         entry2.or_insert(scalar_ptr);
         scalar_ptr
     }
 
+    /// Whether `a` and `b` are copies of one expression, which only a store created with `with_cons_arena` holds:
+    /// equal, or conses, funs, thunks or commitments whose children are themselves copies.
+    fn are_copies(&self, a: Ptr<F>, b: Ptr<F>) -> bool {
+        self.copies_aux(vec![(a, b)], Vec::new())
+    }
+
+    /// Like `are_copies`, for continuations: equal, or of the same kind and operator with children that are copies.
+    fn are_cont_copies(&self, a: ContPtr<F>, b: ContPtr<F>) -> bool {
+        self.copies_aux(Vec::new(), vec![(a, b)])
+    }
+
+    fn copies_aux(
+        &self,
+        mut exprs: Vec<(Ptr<F>, Ptr<F>)>,
+        mut conts: Vec<(ContPtr<F>, ContPtr<F>)>,
+    ) -> bool {
+        use Continuation::*;
+
+        if self.dedups_conses() {
+            return false;
+        }
+        loop {
+            if let Some((a, b)) = exprs.pop() {
+                if a == b {
+                    continue;
+                }
+                if a.tag() != b.tag() || a.is_opaque() || b.is_opaque() {
+                    return false;
+                }
+                match a.tag() {
+                    ExprTag::Cons => match (self.fetch_cons(&a), self.fetch_cons(&b)) {
+                        (Some((a0, a1)), Some((b0, b1))) => exprs.extend([(*a0, *b0), (*a1, *b1)]),
+                        _ => return false,
+                    },
+                    ExprTag::Fun => match (self.fetch_fun(&a), self.fetch_fun(&b)) {
+                        (Some((a0, a1, a2)), Some((b0, b1, b2))) => {
+                            exprs.extend([(*a0, *b0), (*a1, *b1), (*a2, *b2)])
+                        }
+                        _ => return false,
+                    },
+                    ExprTag::Thunk => match (self.fetch_thunk(&a), self.fetch_thunk(&b)) {
+                        (Some(a), Some(b)) => {
+                            exprs.push((a.value, b.value));
+                            conts.push((a.continuation, b.continuation));
+                        }
+                        _ => return false,
+                    },
+                    ExprTag::Comm => match (self.fetch_comm(&a), self.fetch_comm(&b)) {
+                        (Some((a_secret, a)), Some((b_secret, b))) if a_secret == b_secret => {
+                            exprs.push((*a, *b))
+                        }
+                        _ => return false,
+                    },
+                    _ => return false,
+                }
+            } else if let Some((a, b)) = conts.pop() {
+                if a == b {
+                    continue;
+                }
+                if a.0 != b.0 || a.1.is_opaque() || b.1.is_opaque() {
+                    return false;
+                }
+                let (Some(a), Some(b)) = (self.fetch_cont(&a), self.fetch_cont(&b)) else {
+                    return false;
+                };
+                let same_operator = match (&a, &b) {
+                    (Unop { operator: x, .. }, Unop { operator: y, .. }) => x == y,
+                    (Binop { operator: x, .. }, Binop { operator: y, .. })
+                    | (Binop2 { operator: x, .. }, Binop2 { operator: y, .. }) => x == y,
+                    _ => true,
+                };
+                if !same_operator {
+                    return false;
+                }
+                let ((a_exprs, a_cont), (b_exprs, b_cont)) = (a.children(), b.children());
+                exprs.extend(a_exprs.into_iter().zip(b_exprs));
+                conts.extend(a_cont.zip(b_cont));
+            } else {
+                return true;
+            }
+        }
+    }
+
     fn get_scalar_ptr(&self, ptr: Ptr<F>, hash: F) -> ScalarPtr<F> {
         ScalarPtr::from_parts(ptr.0, hash)
     }
@@ -1959,7 +4285,14 @@ impl<F: LurkField> Store<F> {
     /// ensure that they are cached properly
     fn create_cont_scalar_ptr(&self, ptr: ContPtr<F>, hash: F) -> ScalarContPtr<F> {
         let scalar_ptr = ScalarContPtr::from_parts(ptr.0, hash);
-        self.scalar_ptr_cont_map.entry(scalar_ptr).or_insert(ptr);
+        let existing = *self.scalar_ptr_cont_map.entry(scalar_ptr).or_insert(ptr);
+        debug_assert!(
+            existing == ptr
+                || existing.1.is_opaque()
+                || ptr.1.is_opaque()
+                || self.are_cont_copies(existing, ptr),
+            "hash collision: {existing:?} and {ptr:?} both hash to {scalar_ptr}"
+        );
 
         scalar_ptr
     }
@@ -1973,68 +4306,85 @@ impl<F: LurkField> Store<F> {
     }
 
     pub fn get_hash_components_cont(&self, ptr: &ContPtr<F>) -> Option<[F; 8]> {
-        use Continuation::*;
-
         let cont = self.fetch_cont(ptr)?;
+        self.get_hash_components_continuation(&cont)
+    }
 
-        let hash = match &cont {
+    /// The hash components of `cont`, which need not be interned, though its children must be.
+    pub fn get_hash_components_continuation(&self, cont: &Continuation<F>) -> Option<[F; 8]> {
+        self.continuation_hash_components(self, cont)
+    }
+
+    /// The hash components of `cont`, taking the hashes of its children from `children`.
+    fn continuation_hash_components(
+        &self,
+        children: &impl ChildHashes<F>,
+        cont: &Continuation<F>,
+    ) -> Option<[F; 8]> {
+        use Continuation::*;
+
+        let hash = match cont {
             Outermost | Terminal | Dummy | Error => self.get_hash_components_default(),
             Call0 {
                 saved_env,
                 continuation,
-            } => self.get_hash_components_call0(saved_env, continuation)?,
+            } => self.get_hash_components_call0(children, saved_env, continuation)?,
             Call {
                 unevaled_arg,
                 saved_env,
                 continuation,
-            } => self.get_hash_components_call(unevaled_arg, saved_env, continuation)?,
+            } => self.get_hash_components_call(children, unevaled_arg, saved_env, continuation)?,
             Call2 {
                 function,
                 saved_env,
                 continuation,
-            } => self.get_hash_components_call2(function, saved_env, continuation)?,
+            } => self.get_hash_components_call2(children, function, saved_env, continuation)?,
             Tail {
                 saved_env,
                 continuation,
-            } => self.get_hash_components_tail(saved_env, continuation)?,
+            } => self.get_hash_components_tail(children, saved_env, continuation)?,
             Lookup {
                 saved_env,
                 continuation,
-            } => self.get_hash_components_lookup(saved_env, continuation)?,
+            } => self.get_hash_components_lookup(children, saved_env, continuation)?,
             Unop {
                 operator,
                 continuation,
-            } => self.get_hash_components_unop(operator, continuation)?,
+            } => self.get_hash_components_unop(children, operator, continuation)?,
             Binop {
                 operator,
                 saved_env,
                 unevaled_args,
                 continuation,
-            } => {
-                self.get_hash_components_binop(operator, saved_env, unevaled_args, continuation)?
-            }
-            Binop2 {
+            } => self.get_hash_components_binop(
+                children,
+                operator,
+                saved_env,
+                unevaled_args,
+                continuation,
+            )?,
+            Binop2 {
                 operator,
                 evaled_arg,
                 continuation,
-            } => self.get_hash_components_binop2(operator, evaled_arg, continuation)?,
+            } => self.get_hash_components_binop2(children, operator, evaled_arg, continuation)?,
             If {
                 unevaled_args,
                 continuation,
-            } => self.get_hash_components_if(unevaled_args, continuation)?,
+            } => self.get_hash_components_if(children, unevaled_args, continuation)?,
             Let {
                 var,
                 body,
                 saved_env,
                 continuation,
-            } => self.get_hash_components_let(var, body, saved_env, continuation)?,
+            } => self.get_hash_components_let(children, var, body, saved_env, continuation)?,
             LetRec {
                 var,
                 body,
                 saved_env,
                 continuation,
-            } => self.get_hash_components_let_rec(var, body, saved_env, continuation)?,
-            Emit { continuation } => self.get_hash_components_emit(continuation)?,
+            } => self.get_hash_components_let_rec(children, var, body, saved_env, continuation)?,
+            Emit { continuation } => self.get_hash_components_emit(children, continuation)?,
         };
 
         Some([
@@ -2045,150 +4395,191 @@ impl<F: LurkField> Store<F> {
 
     fn get_hash_components_let_rec(
         &self,
+        children: &impl ChildHashes<F>,
         var: &Ptr<F>,
         body: &Ptr<F>,
         saved_env: &Ptr<F>,
         cont: &ContPtr<F>,
     ) -> Option<[[F; 2]; 4]> {
-        let var = self.get_expr_hash(var)?.into_hash_components();
-        let body = self.get_expr_hash(body)?.into_hash_components();
-        let saved_env = self.get_expr_hash(saved_env)?.into_hash_components();
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let var = children.expr_hash(var)?.into_hash_components();
+        let body = children.expr_hash(body)?.into_hash_components();
+        let saved_env = children.expr_hash(saved_env)?.into_hash_components();
+        let cont = children.cont_hash(cont)?.into_hash_components();
         Some([var, body, saved_env, cont])
     }
 
     fn get_hash_components_let(
         &self,
+        children: &impl ChildHashes<F>,
         var: &Ptr<F>,
         body: &Ptr<F>,
         saved_env: &Ptr<F>,
         cont: &ContPtr<F>,
     ) -> Option<[[F; 2]; 4]> {
-        let var = self.get_expr_hash(var)?.into_hash_components();
-        let body = self.get_expr_hash(body)?.into_hash_components();
-        let saved_env = self.get_expr_hash(saved_env)?.into_hash_components();
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let var = children.expr_hash(var)?.into_hash_components();
+        let body = children.expr_hash(body)?.into_hash_components();
+        let saved_env = children.expr_hash(saved_env)?.into_hash_components();
+        let cont = children.cont_hash(cont)?.into_hash_components();
         Some([var, body, saved_env, cont])
     }
 
     fn get_hash_components_if(
         &self,
+        children: &impl ChildHashes<F>,
         unevaled_args: &Ptr<F>,
         cont: &ContPtr<F>,
     ) -> Option<[[F; 2]; 4]> {
         let def = [F::zero(), F::zero()];
-        let unevaled_args = self.get_expr_hash(unevaled_args)?.into_hash_components();
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let unevaled_args = children.expr_hash(unevaled_args)?.into_hash_components();
+        let cont = children.cont_hash(cont)?.into_hash_components();
         Some([unevaled_args, cont, def, def])
     }
 
     fn get_hash_components_binop2(
         &self,
+        children: &impl ChildHashes<F>,
         op: &Op2,
         arg1: &Ptr<F>,
         cont: &ContPtr<F>,
     ) -> Option<[[F; 2]; 4]> {
         let def = [F::zero(), F::zero()];
-        let op = [op.to_field(), F::zero()];
-        let arg1 = self.get_expr_hash(arg1)?.into_hash_components();
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let op = self.op2_hash_components(op);
+        let arg1 = children.expr_hash(arg1)?.into_hash_components();
+        let cont = children.cont_hash(cont)?.into_hash_components();
         Some([op, arg1, cont, def])
     }
 
     fn get_hash_components_binop(
         &self,
+        children: &impl ChildHashes<F>,
         op: &Op2,
         saved_env: &Ptr<F>,
         unevaled_args: &Ptr<F>,
         cont: &ContPtr<F>,
     ) -> Option<[[F; 2]; 4]> {
-        let op = [op.to_field(), F::zero()];
-        let saved_env = self.get_expr_hash(saved_env)?.into_hash_components();
-        let unevaled_args = self.get_expr_hash(unevaled_args)?.into_hash_components();
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let op = self.op2_hash_components(op);
+        let saved_env = children.expr_hash(saved_env)?.into_hash_components();
+        let unevaled_args = children.expr_hash(unevaled_args)?.into_hash_components();
+        let cont = children.cont_hash(cont)?.into_hash_components();
         Some([op, saved_env, unevaled_args, cont])
     }
 
-    fn get_hash_components_unop(&self, op: &Op1, cont: &ContPtr<F>) -> Option<[[F; 2]; 4]> {
+    /// The pair an `Op1` occupies in the hash preimage of a `Unop` continuation: its tag field and zero, in the
+    /// place of an expression's tag and value.
+    pub fn op1_hash_components(&self, op: &Op1) -> [F; 2] {
+        [op.to_field(), F::zero()]
+    }
+
+    /// The pair an `Op2` occupies in the hash preimage of a `Binop` or `Binop2` continuation, as `op1_hash_components`.
+    pub fn op2_hash_components(&self, op: &Op2) -> [F; 2] {
+        [op.to_field(), F::zero()]
+    }
+
+    fn get_hash_components_unop(
+        &self,
+        children: &impl ChildHashes<F>,
+        op: &Op1,
+        cont: &ContPtr<F>,
+    ) -> Option<[[F; 2]; 4]> {
         let def = [F::zero(), F::zero()];
-        let op = [op.to_field(), F::zero()];
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let op = self.op1_hash_components(op);
+        let cont = children.cont_hash(cont)?.into_hash_components();
         Some([op, cont, def, def])
     }
 
     fn get_hash_components_lookup(
         &self,
+        children: &impl ChildHashes<F>,
         saved_env: &Ptr<F>,
         cont: &ContPtr<F>,
     ) -> Option<[[F; 2]; 4]> {
         let def = [F::zero(), F::zero()];
-        let saved_env = self.get_expr_hash(saved_env)?.into_hash_components();
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let saved_env = children.expr_hash(saved_env)?.into_hash_components();
+        let cont = children.cont_hash(cont)?.into_hash_components();
         Some([saved_env, cont, def, def])
     }
 
     fn get_hash_components_tail(
         &self,
+        children: &impl ChildHashes<F>,
         saved_env: &Ptr<F>,
         cont: &ContPtr<F>,
     ) -> Option<[[F; 2]; 4]> {
         let def = [F::zero(), F::zero()];
-        let saved_env = self.get_expr_hash(saved_env)?.into_hash_components();
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let saved_env = children.expr_hash(saved_env)?.into_hash_components();
+        let cont = children.cont_hash(cont)?.into_hash_components();
         Some([saved_env, cont, def, def])
     }
 
     fn get_hash_components_call0(
         &self,
+        children: &impl ChildHashes<F>,
         saved_env: &Ptr<F>,
         cont: &ContPtr<F>,
     ) -> Option<[[F; 2]; 4]> {
         let def = [F::zero(), F::zero()];
 
-        let saved_env = self.get_expr_hash(saved_env)?.into_hash_components();
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let saved_env = children.expr_hash(saved_env)?.into_hash_components();
+        let cont = children.cont_hash(cont)?.into_hash_components();
 
         Some([saved_env, cont, def, def])
     }
 
     fn get_hash_components_call(
         &self,
+        children: &impl ChildHashes<F>,
         arg: &Ptr<F>,
         saved_env: &Ptr<F>,
         cont: &ContPtr<F>,
     ) -> Option<[[F; 2]; 4]> {
         let def = [F::zero(), F::zero()];
-        let arg = self.get_expr_hash(arg)?.into_hash_components();
-        let saved_env = self.get_expr_hash(saved_env)?.into_hash_components();
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let arg = children.expr_hash(arg)?.into_hash_components();
+        let saved_env = children.expr_hash(saved_env)?.into_hash_components();
+        let cont = children.cont_hash(cont)?.into_hash_components();
 
         Some([saved_env, arg, cont, def])
     }
 
     fn get_hash_components_call2(
         &self,
+        children: &impl ChildHashes<F>,
         fun: &Ptr<F>,
         saved_env: &Ptr<F>,
         cont: &ContPtr<F>,
     ) -> Option<[[F; 2]; 4]> {
         let def = [F::zero(), F::zero()];
-        let fun = self.get_expr_hash(fun)?.into_hash_components();
-        let saved_env = self.get_expr_hash(saved_env)?.into_hash_components();
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let fun = children.expr_hash(fun)?.into_hash_components();
+        let saved_env = children.expr_hash(saved_env)?.into_hash_components();
+        let cont = children.cont_hash(cont)?.into_hash_components();
         Some([saved_env, fun, cont, def])
     }
 
-    fn get_hash_components_emit(&self, cont: &ContPtr<F>) -> Option<[[F; 2]; 4]> {
+    fn get_hash_components_emit(
+        &self,
+        children: &impl ChildHashes<F>,
+        cont: &ContPtr<F>,
+    ) -> Option<[[F; 2]; 4]> {
         let def = [F::zero(), F::zero()];
 
-        let cont = self.hash_cont(cont)?.into_hash_components();
+        let cont = children.cont_hash(cont)?.into_hash_components();
 
         Some([cont, def, def, def])
     }
 
     pub fn get_hash_components_thunk(&self, thunk: &Thunk<F>) -> Option<[F; 4]> {
-        let value_hash = self.get_expr_hash(&thunk.value)?.into_hash_components();
-        let continuation_hash = self.hash_cont(&thunk.continuation)?.into_hash_components();
+        self.thunk_hash_components(self, thunk)
+    }
+
+    /// The hash components of `thunk`, taking the hashes of its value and continuation from `children`.
+    fn thunk_hash_components(
+        &self,
+        children: &impl ChildHashes<F>,
+        thunk: &Thunk<F>,
+    ) -> Option<[F; 4]> {
+        let value_hash = children.expr_hash(&thunk.value)?.into_hash_components();
+        let continuation_hash = children
+            .cont_hash(&thunk.continuation)?
+            .into_hash_components();
 
         Some([
             value_hash[0],
@@ -2267,9 +4658,34 @@ impl<F: LurkField> Store<F> {
         Some(self.scalar_ptr(comm, hashed, mode))
     }
 
+    /// Hash two field elements exactly as the store hashes a cons whose car and cdr have values `a` and `b` and zero
+    /// tags, through the Poseidon cache. Under `PairHash::Arity4` the preimage is `[0, a, 0, b]`, hashed with the
+    /// arity-4 constants; under `PairHash::Arity3` it is `[0, a, b]`, the packed tags being zero, hashed with the
+    /// arity-3 constants.
+    pub fn poseidon_hash2(&self, a: F, b: F) -> F {
+        let zero_tag = |value| ScalarPtr::from_parts(ExprTag::Nil, value);
+        self.hash_scalar_ptrs_2(&[zero_tag(a), zero_tag(b)])
+    }
+
+    /// Hash three field elements with the arity-3 constants, unpadded. This is the layout of commitments:
+    /// `[secret, payload_tag, payload_value]`.
+    pub fn poseidon_hash3(&self, a: F, b: F, c: F) -> F {
+        self.poseidon_cache.hash3(&[a, b, c])
+    }
+
+    /// Hash four field elements with the arity-4 constants, unpadded. This is the layout of conses and thunks:
+    /// `[car_tag, car_value, cdr_tag, cdr_value]`.
+    pub fn poseidon_hash4(&self, preimage: [F; 4]) -> F {
+        self.poseidon_cache.hash4(&preimage)
+    }
+
+    /// Hash precomputed expression components, such as those of a thunk, or of a cons under `PairHash::Arity4`.
+    pub fn hash_expr_components(&self, components: &[F; 4]) -> F {
+        self.poseidon_hash4(*components)
+    }
+
     pub(crate) fn commitment_hash(&self, secret_scalar: F, payload: ScalarPtr<F>) -> F {
-        let preimage = [secret_scalar, payload.0.to_field(), payload.1];
-        self.poseidon_cache.hash3(&preimage)
+        commitment_hash_in(&self.poseidon_cache, secret_scalar, payload)
     }
 
     fn hash_thunk(&self, ptr: Ptr<F>, mode: HashScalar) -> Option<ScalarPtr<F>> {
@@ -2278,6 +4694,22 @@ impl<F: LurkField> Store<F> {
         Some(self.scalar_ptr(ptr, self.poseidon_cache.hash4(&components), mode))
     }
 
+    /// The `ScalarPtr` of the thunk of `value` and `continuation`, which need not be interned in the thunk store.
+    pub fn hash_thunk_value(
+        &self,
+        value: &Ptr<F>,
+        continuation: &ContPtr<F>,
+    ) -> Option<ScalarPtr<F>> {
+        let components = self.get_hash_components_thunk(&Thunk {
+            value: *value,
+            continuation: *continuation,
+        })?;
+        Some(ScalarPtr::from_parts(
+            ExprTag::Thunk,
+            self.poseidon_cache.hash4(&components),
+        ))
+    }
+
     fn hash_char(&self, ptr: Ptr<F>, mode: HashScalar) -> Option<ScalarPtr<F>> {
         let char_code = ptr.1 .0 .0 as u32;
 
@@ -2290,6 +4722,12 @@ impl<F: LurkField> Store<F> {
         Some(self.scalar_ptr(ptr, n.into_scalar(), mode))
     }
 
+    /// The `ScalarPtr` of the number `n`, which is its own value. Unlike `hash_expr`, this neither requires `n` to be
+    /// interned nor touches the reverse map.
+    pub fn hash_num_value(&self, n: &Num<F>) -> ScalarPtr<F> {
+        ScalarPtr::from_parts(ExprTag::Num, n.into_scalar())
+    }
+
     fn hash_uint(&self, ptr: Ptr<F>, mode: HashScalar) -> Option<ScalarPtr<F>> {
         let n = self.fetch_uint(&ptr)?;
 
@@ -2356,6 +4794,9 @@ impl<F: LurkField> Store<F> {
         if s.is_empty() {
             return F::zero();
         };
+        if let Some(hash) = self.poseidon_cache.hash_string(s) {
+            return hash;
+        }
         let mut chars = s.chars();
         let char = chars.next().unwrap();
         let rest_string = chars.collect::<String>();
@@ -2385,14 +4826,17 @@ impl<F: LurkField> Store<F> {
 
     // All hashes of substrings, shortest to longest.
     fn all_hashes(&mut self, s: &str, initial_scalar_ptr: ScalarPtr<F>) -> Vec<F> {
-        let chars = s.chars().rev();
+        let chars = s.char_indices().rev();
         let mut hashes = Vec::with_capacity(s.len());
 
-        chars.fold(initial_scalar_ptr, |acc, char| {
+        chars.fold(initial_scalar_ptr, |acc, (i, char)| {
             let c_scalar: F = (u32::from(char) as u64).into();
             // This bypasses create_scalar_ptr but is okay because Chars are immediate and don't need to be indexed.
             let c = ScalarPtr::from_parts(ExprTag::Char, c_scalar);
-            let hash = self.hash_scalar_ptrs_2(&[c, acc]);
+            let hash = self
+                .poseidon_cache
+                .hash_string(&s[i..])
+                .unwrap_or_else(|| self.hash_scalar_ptrs_2(&[c, acc]));
             // This bypasses create_scalar_ptr but is okay because we will call it to correctly create each of these
             // ScalarPtrs below, in hash_string_mut_aux.
             let new_scalar_ptr = ScalarPtr::from_parts(ExprTag::Str, hash);
@@ -2436,16 +4880,34 @@ impl<F: LurkField> Store<F> {
     }
 
     fn hash_scalar_ptrs_2(&self, ptrs: &[ScalarPtr<F>; 2]) -> F {
-        let preimage = [
-            ptrs[0].0.to_field::<F>(),
-            ptrs[0].1,
-            ptrs[1].0.to_field::<F>(),
-            ptrs[1].1,
-        ];
-        self.poseidon_cache.hash4(&preimage)
+        self.hash_scalar_ptrs_2_in(&self.poseidon_cache, ptrs)
+    }
+
+    /// Like `hash_scalar_ptrs_2`, hashing through `cache` rather than the store's own.
+    fn hash_scalar_ptrs_2_in(&self, cache: &PoseidonCache<F>, ptrs: &[ScalarPtr<F>; 2]) -> F {
+        match self.pair_hash {
+            PairHash::Arity4 => {
+                let preimage = [
+                    ptrs[0].0.to_field::<F>(),
+                    ptrs[0].1,
+                    ptrs[1].0.to_field::<F>(),
+                    ptrs[1].1,
+                ];
+                cache.hash4(&preimage)
+            }
+            PairHash::Arity3 => {
+                let tags = ptrs[0].0.to_field::<F>() + ptrs[1].0.to_field::<F>() * F::from(1 << 16);
+                cache.hash3(&[tags, ptrs[0].1, ptrs[1].1])
+            }
+        }
     }
 
     fn hash_scalar_ptrs_3(&self, ptrs: &[ScalarPtr<F>; 3]) -> F {
+        self.hash_scalar_ptrs_3_in(&self.poseidon_cache, ptrs)
+    }
+
+    /// Like `hash_scalar_ptrs_3`, hashing through `cache` rather than the store's own.
+    fn hash_scalar_ptrs_3_in(&self, cache: &PoseidonCache<F>, ptrs: &[ScalarPtr<F>; 3]) -> F {
         let preimage = [
             ptrs[0].0.to_field::<F>(),
             ptrs[0].1,
@@ -2454,7 +4916,60 @@ impl<F: LurkField> Store<F> {
             ptrs[2].0.to_field::<F>(),
             ptrs[2].1,
         ];
-        self.poseidon_cache.hash6(&preimage)
+        cache.hash6(&preimage)
+    }
+
+    /// The `ScalarPtr` a cons of `car` and `cdr` would have, computed without interning the cons. The children's
+    /// own hashes are cached as usual.
+    pub fn would_be_cons_hash(&self, car: &Ptr<F>, cdr: &Ptr<F>) -> Option<ScalarPtr<F>> {
+        let hash = self.hash_ptrs_2(&[*car, *cdr], HashScalar::Create)?;
+        Some(ScalarPtr::from_parts(ExprTag::Cons, hash))
+    }
+
+    /// The `ScalarPtr` a function with `arg`, `body` and `closed_env` would have, computed without interning it.
+    pub fn would_be_fun_hash(
+        &self,
+        arg: &Ptr<F>,
+        body: &Ptr<F>,
+        closed_env: &Ptr<F>,
+    ) -> Option<ScalarPtr<F>> {
+        let hash = self.hash_ptrs_3(&[*arg, *body, *closed_env], HashScalar::Create)?;
+        Some(ScalarPtr::from_parts(ExprTag::Fun, hash))
+    }
+
+    /// The `ScalarPtr` of `expr`, computed without interning it. Children must be interned, and so must symbols and
+    /// strings, whose hashes are over their interned suffixes and path segments; `None` is returned otherwise.
+    pub fn hash_expression(&self, expr: &Expression<'_, F>) -> Option<ScalarPtr<F>> {
+        match expr {
+            Expression::Nil => self.hash_nil(HashScalar::Create),
+            Expression::Cons(car, cdr) => self.would_be_cons_hash(car, cdr),
+            Expression::Comm(secret, payload) => {
+                let payload = self.hash_expr(payload)?;
+                Some(ScalarPtr::from_parts(
+                    ExprTag::Comm,
+                    self.commitment_hash(*secret, payload),
+                ))
+            }
+            Expression::Sym(sym) => {
+                let ptr = self.try_get_sym_by_full_name(sym.full_name())?;
+                self.hash_sym(ptr, HashScalar::Create)
+            }
+            Expression::Fun(arg, body, closed_env) => self.would_be_fun_hash(arg, body, closed_env),
+            Expression::Num(num) => Some(self.hash_num_value(num)),
+            Expression::Str(str) => {
+                let ptr = self.get_str(str)?;
+                self.hash_str(ptr, HashScalar::Create)
+            }
+            Expression::Thunk(thunk) => self.hash_thunk_value(&thunk.value, &thunk.continuation),
+            Expression::Opaque(ptr) => self.hash_expr(ptr),
+            Expression::Char(c) => Some(ScalarPtr::from_parts(
+                ExprTag::Char,
+                F::from(u32::from(*c) as u64),
+            )),
+            Expression::UInt(UInt::U64(n)) => {
+                Some(ScalarPtr::from_parts(ExprTag::U64, F::from_u64(*n)))
+            }
+        }
     }
 
     pub fn hash_nil(&self, mode: HashScalar) -> Option<ScalarPtr<F>> {
@@ -2491,6 +5006,71 @@ impl<F: LurkField> Store<F> {
         }
     }
 
+    /// Whether `a` and `b` are the same entry of this store: same tag and same `RawPtr`. Unlike `ptr_eq`, which
+    /// compares hashes, this distinguishes two copies of an expression, so it tells whether a subtree is shared.
+    pub fn shares_subtree(&self, a: &Ptr<F>, b: &Ptr<F>) -> bool {
+        a == b
+    }
+
+    /// Structural equality of `a` and `b`, folding case when comparing symbol and keyword names, so that `Foo` and
+    /// `foo` are equal even in `CaseMode::Preserve`. Conses, funs and commitments are compared recursively; any other
+    /// expression, and any opaque one, is compared by hash.
+    pub fn ptr_equal_ci(&self, a: &Ptr<F>, b: &Ptr<F>) -> bool {
+        let mut pending = vec![(*a, *b)];
+
+        while let Some((a, b)) = pending.pop() {
+            if a == b {
+                continue;
+            }
+            if a.0 != b.0 {
+                return false;
+            }
+            if a.is_opaque() || b.is_opaque() {
+                match (self.get_expr_hash(&a), self.get_expr_hash(&b)) {
+                    (Some(a_hash), Some(b_hash)) if a_hash == b_hash => continue,
+                    _ => return false,
+                }
+            }
+            match a.0 {
+                ExprTag::Sym | ExprTag::Key => {
+                    match (self.sym_name_cow(&a), self.sym_name_cow(&b)) {
+                        (Some(a_name), Some(b_name))
+                            if a_name.to_lowercase() == b_name.to_lowercase() => {}
+                        _ => return false,
+                    }
+                }
+                ExprTag::Cons => match (self.fetch_cons(&a), self.fetch_cons(&b)) {
+                    (Some((a_car, a_cdr)), Some((b_car, b_cdr))) => {
+                        pending.push((*a_cdr, *b_cdr));
+                        pending.push((*a_car, *b_car));
+                    }
+                    _ => return false,
+                },
+                ExprTag::Fun => match (self.fetch_fun(&a), self.fetch_fun(&b)) {
+                    (Some((a_arg, a_body, a_env)), Some((b_arg, b_body, b_env))) => {
+                        pending.push((*a_env, *b_env));
+                        pending.push((*a_body, *b_body));
+                        pending.push((*a_arg, *b_arg));
+                    }
+                    _ => return false,
+                },
+                ExprTag::Comm => match (self.fetch_comm(&a), self.fetch_comm(&b)) {
+                    (Some((a_secret, a_payload)), Some((b_secret, b_payload)))
+                        if a_secret == b_secret =>
+                    {
+                        pending.push((*a_payload, *b_payload));
+                    }
+                    _ => return false,
+                },
+                _ => match (self.get_expr_hash(&a), self.get_expr_hash(&b)) {
+                    (Some(a_hash), Some(b_hash)) if a_hash == b_hash => (),
+                    _ => return false,
+                },
+            }
+        }
+        true
+    }
+
     pub fn cons_eq(&self, a: &Ptr<F>, b: &Ptr<F>) -> bool {
         assert_eq!(ExprTag::Cons, a.tag());
         assert_eq!(ExprTag::Cons, b.tag());
@@ -2504,11 +5084,398 @@ impl<F: LurkField> Store<F> {
         self.get_expr_hash(a) == self.get_expr_hash(b)
     }
 
+    /// Hash `ptr` as `hash_expr` does, also returning every hash computed along the way, in order, for comparison with
+    /// the circuit's. Nothing is taken from, or added to, this store's caches and reverse maps: `ptr` is hashed in a
+    /// separate pass with its own Poseidon cache and memo, so this is slow and meant for debugging only. A preimage
+    /// occurring more than once is hashed, and recorded, once. Opaque pointers contribute the hashes they were interned
+    /// with, without a step.
+    pub fn hash_expr_traced(&self, ptr: &Ptr<F>) -> (Option<ScalarPtr<F>>, Vec<HashStep<F>>) {
+        let hasher = Arc::new(TracingHasher {
+            inner: self.poseidon_cache.hasher.clone(),
+            steps: Default::default(),
+        });
+        let pass = TracedHashing {
+            store: self,
+            cache: PoseidonCache::with_hasher(hasher.clone()),
+            exprs: Default::default(),
+            conts: Default::default(),
+        };
+        let scalar_ptr = pass.expr_hash(ptr);
+        let steps = std::mem::take(&mut *hasher.steps.lock().unwrap());
+        (scalar_ptr, steps)
+    }
+
+    /// Empty the Poseidon cache, which can grow very large. The reverse maps and the store's data are untouched, and
+    /// subsequent hashing recomputes the same values.
+    pub fn clear_poseidon_cache(&self) {
+        self.poseidon_cache.clear();
+    }
+
+    /// Number of cached arity-4, arity-6 and arity-8 Poseidon hashes.
+    pub fn cache_sizes(&self) -> (usize, usize, usize) {
+        let cache = &self.poseidon_cache;
+        (cache.a4.len(), cache.a6.len(), cache.a8.len())
+    }
+
+    /// Fraction, in `[0, 1]`, of the cons, fun, thunk and continuation entries that `hydrate_scalar_cache` has hashed:
+    /// 0 when none has been, 1 right after a full hydration. Nothing is hashed; this only counts the entries still
+    /// awaiting hydration, so entries hashed on demand since the last hydration still count as cold.
+    pub fn cache_warmth(&self) -> f64 {
+        let total = self.cons_store.len()
+            + self.fun_store.len()
+            + self.thunk_store.len()
+            + self.call0_store.len()
+            + self.call_store.len()
+            + self.call2_store.len()
+            + self.tail_store.len()
+            + self.lookup_store.len()
+            + self.unop_store.len()
+            + self.binop_store.len()
+            + self.binop2_store.len()
+            + self.if_store.len()
+            + self.let_store.len()
+            + self.letrec_store.len()
+            + self.emit_store.len();
+        if total == 0 {
+            return 1.0;
+        }
+
+        let cold_exprs = self
+            .dehydrated
+            .iter()
+            .filter(|ptr| {
+                matches!(ptr.tag(), ExprTag::Cons | ExprTag::Fun | ExprTag::Thunk)
+                    && !ptr.is_opaque()
+            })
+            .count();
+        // Simple continuations have no entry of their own, and are marked dehydrated every time they are interned.
+        let cold_conts = self
+            .dehydrated_cont
+            .iter()
+            .filter(|ptr| {
+                !matches!(
+                    ptr.tag(),
+                    ContTag::Outermost | ContTag::Terminal | ContTag::Error | ContTag::Dummy
+                )
+            })
+            .count();
+        let cold = (cold_exprs + cold_conts).min(total);
+
+        (total - cold) as f64 / total as f64
+    }
+
+    /// Number of entries in the `ScalarPtr -> Ptr` and `ScalarContPtr -> ContPtr` reverse maps.
+    pub fn reverse_map_sizes(&self) -> (usize, usize) {
+        (self.scalar_ptr_map.len(), self.scalar_ptr_cont_map.len())
+    }
+
+    /// Empty the `ScalarPtr -> Ptr` and `ScalarContPtr -> ContPtr` reverse maps, along with the `Ptr -> ScalarPtr`
+    /// cache that would otherwise stop hashing from repopulating them, leaving the Poseidon cache and the store's data
+    /// untouched. Hashing repopulates them. Entries for opaque pointers are kept, since their hashes cannot be
+    /// recomputed.
+    pub fn clear_reverse_maps(&self) {
+        self.pointer_scalar_ptr_cache
+            .retain(|ptr, _| ptr.is_opaque());
+        self.scalar_ptr_map.retain(|_, ptr| ptr.is_opaque());
+        self.scalar_ptr_cont_map.clear();
+    }
+
+    /// Remove the entries for pointers tagged `tag` from the `Ptr -> ScalarPtr` cache and the `ScalarPtr -> Ptr` reverse
+    /// map, leaving those of other tags, the Poseidon cache and the primary data intact. Entries for opaque pointers
+    /// are kept, since their hashes cannot be recomputed.
+    pub fn clear_cache_for_tag(&self, tag: ExprTag) {
+        let clear = |ptr: &Ptr<F>| ptr.tag() == tag && !ptr.is_opaque();
+        self.pointer_scalar_ptr_cache.retain(|ptr, _| !clear(ptr));
+        self.scalar_ptr_map.retain(|_, ptr| !clear(ptr));
+    }
+
+    /// A copy of the store's entries and configuration with empty caches: no Poseidon hashes, no reverse or forward
+    /// `ScalarPtr` maps. Substores are copied without spare capacity. Every entry of the copy is dehydrated, so
+    /// `hydrate_scalar_cache` restores the caches; until then, hashes are recomputed on demand.
+    pub fn clone_without_caches(&self) -> Store<F> {
+        let expr = |tag, idx| Ptr(tag, RawPtr::new(idx));
+        let ptrs = |tag, len| (0..len).map(move |idx| expr(tag, idx));
+        let nil_idx = self.get_nil().1.idx();
+        let sym_tag = |idx| {
+            if idx == nil_idx && !self.nil_as_symbol {
+                ExprTag::Nil
+            } else {
+                ExprTag::Sym
+            }
+        };
+        let dehydrated = ptrs(ExprTag::Cons, self.cons_store.len())
+            .chain(ptrs(ExprTag::Comm, self.comm_store.len()))
+            .chain(ptrs(ExprTag::Fun, self.fun_store.len()))
+            .chain(ptrs(ExprTag::Thunk, self.thunk_store.len()))
+            .chain(self.sym_store.0.into_iter().map(|(symbol, _)| {
+                let idx = symbol.to_usize();
+                expr(sym_tag(idx), idx)
+            }))
+            .chain(
+                self.str_store
+                    .0
+                    .into_iter()
+                    .map(|(symbol, _)| expr(ExprTag::Str, symbol.to_usize())),
+            )
+            .chain(self.opaque_map.iter().map(|entry| *entry.key()))
+            .collect();
+
+        Store {
+            cons_store: self.cons_store.shrunk_clone(),
+            comm_store: shrunk_clone(&self.comm_store),
+            fun_store: shrunk_clone(&self.fun_store),
+            sym_store: self.sym_store.shrunk_clone(),
+            num_store: shrunk_clone(&self.num_store),
+            str_store: self.str_store.shrunk_clone(),
+            thunk_store: shrunk_clone(&self.thunk_store),
+            call0_store: shrunk_clone(&self.call0_store),
+            call_store: shrunk_clone(&self.call_store),
+            call2_store: shrunk_clone(&self.call2_store),
+            tail_store: shrunk_clone(&self.tail_store),
+            lookup_store: shrunk_clone(&self.lookup_store),
+            unop_store: shrunk_clone(&self.unop_store),
+            binop_store: shrunk_clone(&self.binop_store),
+            binop2_store: shrunk_clone(&self.binop2_store),
+            if_store: shrunk_clone(&self.if_store),
+            let_store: shrunk_clone(&self.let_store),
+            letrec_store: shrunk_clone(&self.letrec_store),
+            emit_store: shrunk_clone(&self.emit_store),
+            opaque_map: self.opaque_map.clone(),
+            scalar_ptr_map: Default::default(),
+            scalar_ptr_cont_map: Default::default(),
+            poseidon_cache: PoseidonCache::with_hasher(self.poseidon_cache.hasher.clone()),
+            dehydrated,
+            dehydrated_cont: self.cont_ptrs().collect(),
+            opaque_raw_ptr_count: self.opaque_raw_ptr_count,
+            pointer_scalar_ptr_cache: Default::default(),
+            lurk_package: self.lurk_package.clone(),
+            constants: Default::default(),
+            max_entries: self.max_entries,
+            case_mode: self.case_mode,
+            abbreviate_quote: self.abbreviate_quote,
+            wellknown_syms: self.wellknown_syms.clone(),
+            vocabulary: self.vocabulary.clone(),
+            vocabulary_names: self.vocabulary_names.clone(),
+            sym_renames: self.sym_renames.clone(),
+            sym_rename_index: self.sym_rename_index.clone(),
+            nil_as_symbol: self.nil_as_symbol,
+            pair_hash: self.pair_hash,
+            emit_sink: self.emit_sink.clone(),
+        }
+    }
+
+    /// Check that every child pointer of every compound entry (conses, comms, funs, thunks and continuations)
+    /// resolves in the substore selected by its tag, and that symbol and string indices resolve in their interners.
+    /// Stores reconstructed from untrusted data should be validated before use, since dangling pointers would
+    /// otherwise only surface later as panics.
+    pub fn validate(&self) -> Result<(), StoreError> {
+        for (index, (car, cdr)) in self.cons_store.iter().enumerate() {
+            self.validate_ptr("cons_store", index, car)?;
+            self.validate_ptr("cons_store", index, cdr)?;
+        }
+        for (index, (_secret, payload)) in self.comm_store.iter().enumerate() {
+            self.validate_ptr("comm_store", index, payload)?;
+        }
+        for (index, (arg, body, closed_env)) in self.fun_store.iter().enumerate() {
+            if arg.tag() != ExprTag::Sym {
+                return Err(StoreError::TagMismatch {
+                    store: "fun_store",
+                    index,
+                    tag: arg.tag().to_string(),
+                    expected: "sym#",
+                });
+            }
+            self.validate_ptr("fun_store", index, arg)?;
+            self.validate_ptr("fun_store", index, body)?;
+            self.validate_ptr("fun_store", index, closed_env)?;
+        }
+        for (index, thunk) in self.thunk_store.iter().enumerate() {
+            self.validate_ptr("thunk_store", index, &thunk.value)?;
+            self.validate_cont_ptr("thunk_store", index, &thunk.continuation)?;
+        }
+        for (index, (saved_env, cont)) in self.call0_store.iter().enumerate() {
+            self.validate_ptr("call0_store", index, saved_env)?;
+            self.validate_cont_ptr("call0_store", index, cont)?;
+        }
+        for (index, (arg, saved_env, cont)) in self.call_store.iter().enumerate() {
+            self.validate_ptr("call_store", index, arg)?;
+            self.validate_ptr("call_store", index, saved_env)?;
+            self.validate_cont_ptr("call_store", index, cont)?;
+        }
+        for (index, (function, saved_env, cont)) in self.call2_store.iter().enumerate() {
+            self.validate_ptr("call2_store", index, function)?;
+            self.validate_ptr("call2_store", index, saved_env)?;
+            self.validate_cont_ptr("call2_store", index, cont)?;
+        }
+        for (index, (saved_env, cont)) in self.tail_store.iter().enumerate() {
+            self.validate_ptr("tail_store", index, saved_env)?;
+            self.validate_cont_ptr("tail_store", index, cont)?;
+        }
+        for (index, (saved_env, cont)) in self.lookup_store.iter().enumerate() {
+            self.validate_ptr("lookup_store", index, saved_env)?;
+            self.validate_cont_ptr("lookup_store", index, cont)?;
+        }
+        for (index, (_op, cont)) in self.unop_store.iter().enumerate() {
+            self.validate_cont_ptr("unop_store", index, cont)?;
+        }
+        for (index, (_op, saved_env, unevaled_args, cont)) in self.binop_store.iter().enumerate() {
+            self.validate_ptr("binop_store", index, saved_env)?;
+            self.validate_ptr("binop_store", index, unevaled_args)?;
+            self.validate_cont_ptr("binop_store", index, cont)?;
+        }
+        for (index, (_op, evaled_arg, cont)) in self.binop2_store.iter().enumerate() {
+            self.validate_ptr("binop2_store", index, evaled_arg)?;
+            self.validate_cont_ptr("binop2_store", index, cont)?;
+        }
+        for (index, (unevaled_args, cont)) in self.if_store.iter().enumerate() {
+            self.validate_ptr("if_store", index, unevaled_args)?;
+            self.validate_cont_ptr("if_store", index, cont)?;
+        }
+        for (index, (var, body, saved_env, cont)) in self.let_store.iter().enumerate() {
+            self.validate_ptr("let_store", index, var)?;
+            self.validate_ptr("let_store", index, body)?;
+            self.validate_ptr("let_store", index, saved_env)?;
+            self.validate_cont_ptr("let_store", index, cont)?;
+        }
+        for (index, (var, body, saved_env, cont)) in self.letrec_store.iter().enumerate() {
+            self.validate_ptr("letrec_store", index, var)?;
+            self.validate_ptr("letrec_store", index, body)?;
+            self.validate_ptr("letrec_store", index, saved_env)?;
+            self.validate_cont_ptr("letrec_store", index, cont)?;
+        }
+        for (index, cont) in self.emit_store.iter().enumerate() {
+            self.validate_cont_ptr("emit_store", index, cont)?;
+        }
+        Ok(())
+    }
+
+    /// Like `validate`, for the entry `ptr` points at alone: the children of a cons or a commitment must resolve.
+    fn validate_expr(&self, ptr: &Ptr<F>) -> Result<(), StoreError> {
+        let index = ptr.1.idx();
+        match ptr.tag() {
+            _ if ptr.is_opaque() => (),
+            ExprTag::Cons => {
+                if let Some((car, cdr)) = self.fetch_cons(ptr) {
+                    self.validate_ptr("cons_store", index, car)?;
+                    self.validate_ptr("cons_store", index, cdr)?;
+                }
+            }
+            ExprTag::Comm => {
+                if let Some((_secret, payload)) = self.fetch_comm(ptr) {
+                    self.validate_ptr("comm_store", index, payload)?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn validate_ptr(
+        &self,
+        store: &'static str,
+        index: usize,
+        ptr: &Ptr<F>,
+    ) -> Result<(), StoreError> {
+        if self.is_dangling(ptr) {
+            Err(StoreError::DanglingPtr {
+                store,
+                index,
+                tag: ptr.tag().to_string(),
+                ptr_index: ptr.1.idx(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn validate_cont_ptr(
+        &self,
+        store: &'static str,
+        index: usize,
+        ptr: &ContPtr<F>,
+    ) -> Result<(), StoreError> {
+        if self.is_dangling_cont(ptr) {
+            Err(StoreError::DanglingPtr {
+                store,
+                index,
+                tag: ptr.tag().to_string(),
+                ptr_index: ptr.1.idx(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether `ptr`'s index lies outside the substore selected by its tag, e.g. after deserialization or a remap, as
+    /// opposed to pointing at an entry. Opaque pointers are never dangling.
+    pub fn is_dangling(&self, ptr: &Ptr<F>) -> bool {
+        if ptr.is_opaque() {
+            return false;
+        }
+        let idx = ptr.1.idx();
+        let resolves = |set: &StringSet| {
+            SymbolUsize::try_from_usize(idx)
+                .and_then(|symbol| set.0.resolve(symbol))
+                .is_some()
+        };
+        match ptr.0 {
+            ExprTag::Nil | ExprTag::Sym | ExprTag::Key => !resolves(&self.sym_store),
+            ExprTag::Str => !resolves(&self.str_store),
+            ExprTag::Cons => idx >= self.cons_store.len(),
+            ExprTag::Comm => idx >= self.comm_store.len(),
+            ExprTag::Fun => idx >= self.fun_store.len(),
+            ExprTag::Num => idx >= self.num_store.len(),
+            ExprTag::Thunk => idx >= self.thunk_store.len(),
+            ExprTag::Char => char::from_u32(idx as u32).is_none(),
+            ExprTag::U64 => false,
+        }
+    }
+
+    fn is_dangling_cont(&self, ptr: &ContPtr<F>) -> bool {
+        let idx = ptr.1.idx();
+        match ptr.0 {
+            ContTag::Outermost | ContTag::Error | ContTag::Dummy | ContTag::Terminal => false,
+            ContTag::Call0 => idx >= self.call0_store.len(),
+            ContTag::Call => idx >= self.call_store.len(),
+            ContTag::Call2 => idx >= self.call2_store.len(),
+            ContTag::Tail => idx >= self.tail_store.len(),
+            ContTag::Lookup => idx >= self.lookup_store.len(),
+            ContTag::Unop => idx >= self.unop_store.len(),
+            ContTag::Binop => idx >= self.binop_store.len(),
+            ContTag::Binop2 => idx >= self.binop2_store.len(),
+            ContTag::If => idx >= self.if_store.len(),
+            ContTag::Let => idx >= self.let_store.len(),
+            ContTag::LetRec => idx >= self.letrec_store.len(),
+            ContTag::Emit => idx >= self.emit_store.len(),
+        }
+    }
+
+    /// Pre-size `scalar_ptr_map` and `scalar_ptr_cont_map` for one entry per expression and continuation in the
+    /// store, so that hydrating a large store does not repeatedly grow them.
+    pub fn reserve_scalar_maps(&mut self) {
+        let exprs = self.cons_store.len()
+            + self.comm_store.len()
+            + self.fun_store.len()
+            + self.sym_store.0.len()
+            + self.num_store.len()
+            + self.str_store.0.len()
+            + self.thunk_store.len();
+        let conts = self.entry_count() - exprs;
+
+        reserve_dashmap(&mut self.scalar_ptr_map, exprs);
+        reserve_dashmap(&mut self.scalar_ptr_cont_map, conts);
+    }
+
     /// Fill the cache for Scalars. Only Ptrs which have been interned since last hydration will be hashed, so it is
     /// safe to call this incrementally. However, for best proving performance, we should call exactly once so all
-    /// hashing can be batched, e.g. on the GPU.
+    /// hashing can be batched, e.g. on the GPU. Nums and chars are immediate, their scalar value being the value
+    /// itself, so they are never dehydrated and need no Poseidon work.
     pub fn hydrate_scalar_cache(&mut self) {
+        #[cfg(feature = "tracing")]
+        let (span, start) = (self.hydration_span().entered(), std::time::Instant::now());
+
         self.ensure_constants();
+        self.reserve_scalar_maps();
 
         self.dehydrated.par_iter().for_each(|ptr| {
             self.hash_expr(ptr).expect("failed to hash_expr");
@@ -2523,6 +5490,55 @@ impl<F: LurkField> Store<F> {
         self.dehydrated_cont.truncate(0);
 
         self.dehydrated_cont.clear();
+
+        #[cfg(feature = "tracing")]
+        span.record("duration_us", start.elapsed().as_micros() as u64);
+    }
+
+    /// Hydrate the store, then hash each of `roots` in parallel, returning their `ScalarPtr`s in order. Hydration
+    /// drains the dehydrated lists, so this takes `&mut self`.
+    pub fn hydrate_and_hash_roots(&mut self, roots: &[Ptr<F>]) -> Vec<Option<ScalarPtr<F>>> {
+        self.hydrate_scalar_cache();
+        roots.par_iter().map(|root| self.hash_expr(root)).collect()
+    }
+
+    /// Hydrate the store and freeze it into a `FrozenStore`, which can be shared between threads for concurrent
+    /// reads.
+    pub fn freeze(mut self) -> FrozenStore<F> {
+        self.hydrate_scalar_cache();
+        FrozenStore(self)
+    }
+
+    /// A span for `hydrate_scalar_cache`, recording how many dehydrated entries of each kind are about to be hashed.
+    #[cfg(feature = "tracing")]
+    fn hydration_span(&self) -> tracing::Span {
+        use tracing::field::Empty;
+
+        let span = tracing::info_span!(
+            "hydrate_scalar_cache",
+            cons = Empty,
+            comm = Empty,
+            fun = Empty,
+            thunk = Empty,
+            sym = Empty,
+            str = Empty,
+            cont = Empty,
+            duration_us = Empty,
+        );
+        let count = |tags: &[ExprTag]| {
+            self.dehydrated
+                .iter()
+                .filter(|ptr| tags.contains(&ptr.tag()))
+                .count() as u64
+        };
+        span.record("cons", count(&[ExprTag::Cons]));
+        span.record("comm", count(&[ExprTag::Comm]));
+        span.record("fun", count(&[ExprTag::Fun]));
+        span.record("thunk", count(&[ExprTag::Thunk]));
+        span.record("sym", count(&[ExprTag::Sym, ExprTag::Key, ExprTag::Nil]));
+        span.record("str", count(&[ExprTag::Str]));
+        span.record("cont", self.dehydrated_cont.len() as u64);
+        span
     }
 
     fn ensure_constants(&mut self) {
@@ -2535,6 +5551,24 @@ impl<F: LurkField> Store<F> {
     }
 }
 
+/// Grow `map` so it can hold at least `total` entries. `DashMap::try_reserve` reserves per shard, so instead the map is
+/// rebuilt with the right total capacity, which `with_capacity_and_hasher` spreads across its shards.
+fn reserve_dashmap<K: Eq + Hash, V>(
+    map: &mut dashmap::DashMap<K, V, ahash::RandomState>,
+    total: usize,
+) {
+    if map.capacity() >= total {
+        return;
+    }
+    let old = std::mem::replace(
+        map,
+        dashmap::DashMap::with_capacity_and_hasher(total, Default::default()),
+    );
+    for (k, v) in old {
+        map.insert(k, v);
+    }
+}
+
 impl<F: LurkField> Expression<'_, F> {
     pub fn is_keyword_sym(&self) -> bool {
         match self {
@@ -2564,6 +5598,27 @@ impl<F: LurkField> Expression<'_, F> {
         }
     }
 
+    pub const fn as_num(&self) -> Option<Num<F>> {
+        match self {
+            Expression::Num(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub const fn as_cons(&self) -> Option<(Ptr<F>, Ptr<F>)> {
+        match self {
+            Expression::Cons(car, cdr) => Some((*car, *cdr)),
+            _ => None,
+        }
+    }
+
+    pub const fn as_fun(&self) -> Option<(Ptr<F>, Ptr<F>, Ptr<F>)> {
+        match self {
+            Expression::Fun(arg, body, closed_env) => Some((*arg, *body, *closed_env)),
+            _ => None,
+        }
+    }
+
     pub fn as_simple_keyword_string(&self) -> Option<String> {
         match self {
             Expression::Sym(s) => s.simple_keyword_name(),
@@ -2606,77 +5661,628 @@ impl<F: LurkField> Expression<'_, F> {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
-pub struct ConstantPtrs<F: LurkField>(Option<ScalarPtr<F>>, Ptr<F>);
+/// Copies expressions and continuations from one store into another, for `Store::gc`.
+struct Copier<'a, F: LurkField> {
+    from: &'a Store<F>,
+    to: Store<F>,
+    exprs: HashMap<Ptr<F>, Ptr<F>>,
+    conts: HashMap<ContPtr<F>, ContPtr<F>>,
+}
 
-impl<F: LurkField> ConstantPtrs<F> {
-    pub fn value(&self) -> F {
-        *self.scalar_ptr().value()
-    }
-    pub fn scalar_ptr(&self) -> ScalarPtr<F> {
-        self.0
-            .expect("ScalarPtr missing; hydrate_scalar_cache should have been called.")
-    }
-    pub const fn ptr(&self) -> Ptr<F> {
-        self.1
+impl<'a, F: LurkField> Copier<'a, F> {
+    /// A copier into an empty store configured like `from`.
+    fn new(from: &'a Store<F>) -> Self {
+        Self {
+            from,
+            to: from.empty_like(),
+            exprs: Default::default(),
+            conts: Default::default(),
+        }
     }
-}
 
-#[derive(Clone, Copy, Debug)]
-pub struct NamedConstants<F: LurkField> {
-    pub t: ConstantPtrs<F>,
-    pub nil: ConstantPtrs<F>,
-    pub lambda: ConstantPtrs<F>,
-    pub quote: ConstantPtrs<F>,
-    pub let_: ConstantPtrs<F>,
-    pub letrec: ConstantPtrs<F>,
-    pub cons: ConstantPtrs<F>,
-    pub strcons: ConstantPtrs<F>,
-    pub begin: ConstantPtrs<F>,
-    pub car: ConstantPtrs<F>,
-    pub cdr: ConstantPtrs<F>,
-    pub atom: ConstantPtrs<F>,
-    pub emit: ConstantPtrs<F>,
-    pub sum: ConstantPtrs<F>,
-    pub diff: ConstantPtrs<F>,
-    pub product: ConstantPtrs<F>,
-    pub quotient: ConstantPtrs<F>,
-    pub modulo: ConstantPtrs<F>,
-    pub num_equal: ConstantPtrs<F>,
-    pub equal: ConstantPtrs<F>,
-    pub less: ConstantPtrs<F>,
-    pub less_equal: ConstantPtrs<F>,
-    pub greater: ConstantPtrs<F>,
-    pub greater_equal: ConstantPtrs<F>,
-    pub current_env: ConstantPtrs<F>,
-    pub if_: ConstantPtrs<F>,
-    pub hide: ConstantPtrs<F>,
-    pub commit: ConstantPtrs<F>,
-    pub num: ConstantPtrs<F>,
-    pub u64: ConstantPtrs<F>,
-    pub comm: ConstantPtrs<F>,
-    pub char: ConstantPtrs<F>,
-    pub eval: ConstantPtrs<F>,
-    pub open: ConstantPtrs<F>,
-    pub secret: ConstantPtrs<F>,
-    pub dummy: ConstantPtrs<F>,
-}
+    fn copy_expr(&mut self, ptr: &Ptr<F>) -> Ptr<F> {
+        if let Some(copied) = self.exprs.get(ptr) {
+            return *copied;
+        }
+        if ptr.is_opaque() {
+            let scalar_ptr = *self
+                .from
+                .opaque_map
+                .get(ptr)
+                .expect("ScalarPtr for opaque Ptr missing");
+            let copied = self.to.intern_opaque(ptr.tag(), *scalar_ptr.value());
+            self.exprs.insert(*ptr, copied);
+            return copied;
+        }
 
-impl<F: LurkField> NamedConstants<F> {
-    pub fn new(store: &Store<F>) -> Self {
-        let hash_sym = |name: &str| {
-            let ptr = store.get_lurk_sym(name, true).unwrap();
-            let maybe_scalar_ptr = store.hash_sym(ptr, HashScalar::Get);
-            ConstantPtrs(maybe_scalar_ptr, ptr)
+        let copied = match ptr.tag() {
+            ExprTag::Cons => return self.copy_list(ptr),
+            ExprTag::Nil => self.to.intern_nil(),
+            ExprTag::Sym | ExprTag::Key => {
+                let sym = self.from.fetch_sym(ptr).expect("missing symbol");
+                self.to.intern_sym_by_full_name(sym.full_sym_name())
+            }
+            ExprTag::Str => {
+                let s = self.from.fetch_str(ptr).expect("missing string");
+                self.to.intern_str(s)
+            }
+            ExprTag::Num => {
+                let num = *self.from.fetch_num(ptr).expect("missing num");
+                self.to.intern_num(num)
+            }
+            ExprTag::Char | ExprTag::U64 => *ptr,
+            ExprTag::Comm => {
+                let (secret, payload) = self.from.fetch_comm(ptr).expect("missing comm");
+                let (secret, payload) = (secret.0, *payload);
+                let payload = self.copy_expr(&payload);
+                self.to.intern_comm(secret, payload)
+            }
+            ExprTag::Fun => {
+                let (arg, body, closed_env) = *self.from.fetch_fun(ptr).expect("missing fun");
+                let arg = self.copy_expr(&arg);
+                let body = self.copy_expr(&body);
+                let closed_env = self.copy_expr(&closed_env);
+                self.to.intern_fun(arg, body, closed_env)
+            }
+            ExprTag::Thunk => {
+                let thunk = *self.from.fetch_thunk(ptr).expect("missing thunk");
+                let value = self.copy_expr(&thunk.value);
+                let continuation = self.copy_cont(&thunk.continuation);
+                self.to.intern_thunk(Thunk {
+                    value,
+                    continuation,
+                })
+            }
         };
+        self.exprs.insert(*ptr, copied);
+        copied
+    }
+
+    /// Copy the chain of conses starting at `ptr` iteratively, so long lists don't exhaust the stack.
+    fn copy_list(&mut self, ptr: &Ptr<F>) -> Ptr<F> {
+        let mut conses = Vec::new();
+        let mut tail = *ptr;
+        while tail.tag() == ExprTag::Cons && !tail.is_opaque() && !self.exprs.contains_key(&tail) {
+            let (_, cdr) = self.from.fetch_cons(&tail).expect("missing cons");
+            conses.push(tail);
+            tail = *cdr;
+        }
+        let mut copied = self.copy_expr(&tail);
+        for cons in conses.iter().rev() {
+            let (car, _) = *self.from.fetch_cons(cons).expect("missing cons");
+            let car = self.copy_expr(&car);
+            copied = self.to.intern_cons(car, copied);
+            self.exprs.insert(*cons, copied);
+        }
+        copied
+    }
 
-        let t = hash_sym("t");
-        let nil = ConstantPtrs(
-            Some(store.hash_nil(HashScalar::Get).unwrap()),
-            store.get_nil(),
-        );
-        let lambda = hash_sym("lambda");
-        let quote = hash_sym("quote");
+    fn copy_cont(&mut self, ptr: &ContPtr<F>) -> ContPtr<F> {
+        if let Some(copied) = self.conts.get(ptr) {
+            return *copied;
+        }
+        let cont = self.from.fetch_cont(ptr).expect("missing continuation");
+        let copied = match cont {
+            Continuation::Outermost
+            | Continuation::Error
+            | Continuation::Dummy
+            | Continuation::Terminal => cont,
+            Continuation::Call0 {
+                saved_env,
+                continuation,
+            } => Continuation::Call0 {
+                saved_env: self.copy_expr(&saved_env),
+                continuation: self.copy_cont(&continuation),
+            },
+            Continuation::Call {
+                unevaled_arg,
+                saved_env,
+                continuation,
+            } => Continuation::Call {
+                unevaled_arg: self.copy_expr(&unevaled_arg),
+                saved_env: self.copy_expr(&saved_env),
+                continuation: self.copy_cont(&continuation),
+            },
+            Continuation::Call2 {
+                function,
+                saved_env,
+                continuation,
+            } => Continuation::Call2 {
+                function: self.copy_expr(&function),
+                saved_env: self.copy_expr(&saved_env),
+                continuation: self.copy_cont(&continuation),
+            },
+            Continuation::Tail {
+                saved_env,
+                continuation,
+            } => Continuation::Tail {
+                saved_env: self.copy_expr(&saved_env),
+                continuation: self.copy_cont(&continuation),
+            },
+            Continuation::Lookup {
+                saved_env,
+                continuation,
+            } => Continuation::Lookup {
+                saved_env: self.copy_expr(&saved_env),
+                continuation: self.copy_cont(&continuation),
+            },
+            Continuation::Unop {
+                operator,
+                continuation,
+            } => Continuation::Unop {
+                operator,
+                continuation: self.copy_cont(&continuation),
+            },
+            Continuation::Binop {
+                operator,
+                saved_env,
+                unevaled_args,
+                continuation,
+            } => Continuation::Binop {
+                operator,
+                saved_env: self.copy_expr(&saved_env),
+                unevaled_args: self.copy_expr(&unevaled_args),
+                continuation: self.copy_cont(&continuation),
+            },
+            Continuation::Binop2 {
+                operator,
+                evaled_arg,
+                continuation,
+            } => Continuation::Binop2 {
+                operator,
+                evaled_arg: self.copy_expr(&evaled_arg),
+                continuation: self.copy_cont(&continuation),
+            },
+            Continuation::If {
+                unevaled_args,
+                continuation,
+            } => Continuation::If {
+                unevaled_args: self.copy_expr(&unevaled_args),
+                continuation: self.copy_cont(&continuation),
+            },
+            Continuation::Let {
+                var,
+                body,
+                saved_env,
+                continuation,
+            } => Continuation::Let {
+                var: self.copy_expr(&var),
+                body: self.copy_expr(&body),
+                saved_env: self.copy_expr(&saved_env),
+                continuation: self.copy_cont(&continuation),
+            },
+            Continuation::LetRec {
+                var,
+                saved_env,
+                body,
+                continuation,
+            } => Continuation::LetRec {
+                var: self.copy_expr(&var),
+                saved_env: self.copy_expr(&saved_env),
+                body: self.copy_expr(&body),
+                continuation: self.copy_cont(&continuation),
+            },
+            Continuation::Emit { continuation } => Continuation::Emit {
+                continuation: self.copy_cont(&continuation),
+            },
+        }
+        .intern_aux(&mut self.to);
+        self.conts.insert(*ptr, copied);
+        copied
+    }
+}
+
+/// Builds a store from the entries of a serialized `LightStore`, for `Store::from_reader`. An entry is interned as soon
+/// as the entries its children point at have been, and waits on its first missing child until then.
+struct LightStoreLoader<F: LurkField> {
+    store: Store<F>,
+    waiting: HashMap<ScalarPtr<F>, LightExpr<F>>,
+    dependents: HashMap<ScalarPtr<F>, Vec<ScalarPtr<F>>>,
+}
+
+impl<F: LurkField> LightStoreLoader<F> {
+    fn new() -> Self {
+        Self {
+            store: Store::new(),
+            waiting: Default::default(),
+            dependents: Default::default(),
+        }
+    }
+
+    /// Check the entry for `ptr` on its own, then intern it along with the waiting entries it completes.
+    fn add(&mut self, ptr: ScalarPtr<F>, expr: Option<LightExpr<F>>) -> anyhow::Result<()> {
+        if LightStore::is_ptr_leaf(ptr) {
+            return Err(anyhow!("Leaf pointer found in LightStore: {ptr}"));
+        }
+        if self.waiting.contains_key(&ptr) || self.store.fetch_scalar(&ptr).is_some() {
+            return Ok(());
+        }
+        let Some(expr) = expr else {
+            // opaque data
+            let opaque = self.store.intern_maybe_opaque(ptr.tag(), *ptr.value());
+            self.store.create_scalar_ptr(opaque, *ptr.value());
+            let dependents = self.dependents.remove(&ptr).unwrap_or_default();
+            return self.intern_ready(dependents);
+        };
+        match (ptr.tag(), &expr) {
+            (ExprTag::Nil, LightExpr::Nil)
+            | (ExprTag::Cons, LightExpr::Cons(..))
+            | (ExprTag::Comm, LightExpr::Comm(..)) => (),
+            (ExprTag::Str, LightExpr::StrCons(c, cs)) => {
+                if c.value().to_char().is_none() {
+                    return Err(anyhow!("Non-char head in LightExpr::StrCons"));
+                }
+                if cs.tag() != ExprTag::Str {
+                    return Err(anyhow!("Non-string tail in LightExpr::StrCons"));
+                }
+            }
+            (ExprTag::Sym, LightExpr::SymCons(s, ss)) => {
+                if s.tag() != ExprTag::Str {
+                    return Err(anyhow!("Non-string head in LightExpr::SymCons"));
+                }
+                if ss.tag() != ExprTag::Sym {
+                    return Err(anyhow!("Non-symbol tail in LightExpr::SymCons"));
+                }
+            }
+            (tag, _) => {
+                return Err(anyhow!(
+                    "Unsupported pair of tag and LightExpr: ({tag}, {expr})"
+                ))
+            }
+        }
+        self.waiting.insert(ptr, expr);
+        self.intern_ready(vec![ptr])
+    }
+
+    /// The store built, once every entry has been added.
+    fn finish(self) -> anyhow::Result<Store<F>> {
+        match self.dependents.keys().next() {
+            Some(missing) => Err(anyhow!("LightExpr not found for pointer {missing}")),
+            None => Ok(self.store),
+        }
+    }
+
+    /// Intern each of the waiting entries in `ready` whose children are all known, and in turn the entries that
+    /// were waiting on them.
+    fn intern_ready(&mut self, mut ready: Vec<ScalarPtr<F>>) -> anyhow::Result<()> {
+        while let Some(ptr) = ready.pop() {
+            let expr = self.waiting[&ptr].clone();
+            let mut children = Vec::new();
+            let mut missing = None;
+            for child in Self::children(ptr, &expr) {
+                match self.resolve(child)? {
+                    Some(resolved) => children.push(resolved),
+                    None => {
+                        missing = Some(child);
+                        break;
+                    }
+                }
+            }
+            if let Some(child) = missing {
+                self.dependents.entry(child).or_default().push(ptr);
+                continue;
+            }
+            self.waiting.remove(&ptr);
+            let interned = self.intern(ptr, &expr, &children)?;
+            self.store.validate_expr(&interned)?;
+            ready.extend(self.dependents.remove(&ptr).unwrap_or_default());
+        }
+        Ok(())
+    }
+
+    /// The pointers an entry needs interned before it can be.
+    fn children(ptr: ScalarPtr<F>, expr: &LightExpr<F>) -> Vec<ScalarPtr<F>> {
+        match expr {
+            LightExpr::Cons(x, y) => vec![*x, *y],
+            LightExpr::Comm(_, x) => vec![*x],
+            LightExpr::StrCons(_, cs) => vec![*cs],
+            LightExpr::SymCons(s, ss) => vec![*s, *ss],
+            // We also need the `.LURK.NIL` symbol
+            LightExpr::Nil => vec![ScalarPtr::from_parts(ExprTag::Sym, *ptr.value())],
+        }
+    }
+
+    /// The pointer `ptr` was interned as, interning it first if it is a leaf, or `None` if its entry is yet to come.
+    fn resolve(&mut self, ptr: ScalarPtr<F>) -> anyhow::Result<Option<Ptr<F>>> {
+        if let Some(interned) = self.store.fetch_scalar(&ptr) {
+            return Ok(Some(interned));
+        }
+        if ptr.tag() == ExprTag::Sym {
+            // The `.LURK.NIL` symbol is interned as nil.
+            let nil = ScalarPtr::from_parts(ExprTag::Nil, *ptr.value());
+            if let Some(interned) = self.store.fetch_scalar(&nil) {
+                return Ok(Some(interned));
+            }
+        }
+        if !LightStore::is_ptr_leaf(ptr) {
+            return Ok(None);
+        }
+        let value = *ptr.value();
+        let interned = match ptr.tag() {
+            ExprTag::Num => self.store.intern_num(crate::Num::Scalar(value)),
+            ExprTag::Char => {
+                let c = value
+                    .to_char()
+                    .ok_or_else(|| anyhow!("Invalid char pointer: {ptr}"))?;
+                return Ok(Some(c.into()));
+            }
+            ExprTag::U64 => {
+                let u = value
+                    .to_u64()
+                    .ok_or_else(|| anyhow!("Invalid u64 pointer: {ptr}"))?;
+                self.store.uint64(u)
+            }
+            ExprTag::Str => self.store.intern_str(""),
+            ExprTag::Sym => self.store.intern_sym(&Sym::root()),
+            _ => return Err(anyhow!("Invalid leaf pointer: {ptr}")),
+        };
+        self.store.create_scalar_ptr(interned, value);
+        Ok(Some(interned))
+    }
+
+    /// Intern the entry for `ptr`, given what its children were interned as.
+    fn intern(
+        &mut self,
+        ptr: ScalarPtr<F>,
+        expr: &LightExpr<F>,
+        children: &[Ptr<F>],
+    ) -> anyhow::Result<Ptr<F>> {
+        let interned = match expr {
+            LightExpr::Nil => self.store.intern_nil(),
+            LightExpr::Cons(..) => self.store.intern_cons(children[0], children[1]),
+            LightExpr::Comm(secret, _) => self.store.intern_comm(*secret, children[0]),
+            LightExpr::StrCons(c, _) => {
+                let c = c.value().to_char().expect("head checked by add");
+                let tail = self.string(children[0], ptr)?;
+                self.store.intern_str(format!("{c}{tail}"))
+            }
+            LightExpr::SymCons(..) => {
+                let name = self.string(children[0], ptr)?;
+                if name.contains(SYM_SEPARATOR) {
+                    return Err(anyhow!("Symbol segment with a separator in {ptr}"));
+                }
+                let tail = children[1];
+                if tail.is_opaque() {
+                    return Err(anyhow!("Opaque tail in LightExpr::SymCons for {ptr}"));
+                }
+                let sym = self
+                    .store
+                    .fetch_sym(&tail)
+                    .expect("interned symbol")
+                    .child(name);
+                self.store.intern_sym(&sym)
+            }
+        };
+        self.store.create_scalar_ptr(interned, *ptr.value());
+        Ok(interned)
+    }
+
+    /// The string `str_ptr` was interned as, which `ptr`'s entry needs.
+    fn string(&self, str_ptr: Ptr<F>, ptr: ScalarPtr<F>) -> anyhow::Result<String> {
+        if str_ptr.is_opaque() {
+            return Err(anyhow!("Opaque string in the LightExpr for {ptr}"));
+        }
+        let s = self.store.fetch_str(&str_ptr).expect("interned string");
+        Ok(s.to_string())
+    }
+}
+
+/// Iterator over the elements of a list, returned by `Store::list_iter`.
+pub struct ListIter<'a, F: LurkField> {
+    store: &'a Store<F>,
+    rest: Option<Ptr<F>>,
+    improper_tail: Option<Ptr<F>>,
+}
+
+impl<F: LurkField> ListIter<'_, F> {
+    /// The tail that ended iteration, if it was neither nil nor a cons. Only meaningful once `next` returns `None`.
+    pub fn improper_tail(&self) -> Option<Ptr<F>> {
+        self.improper_tail
+    }
+}
+
+impl<F: LurkField> Iterator for ListIter<'_, F> {
+    type Item = Ptr<F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.rest.take()?;
+        match rest.0 {
+            ExprTag::Nil => None,
+            ExprTag::Cons if !rest.is_opaque() => {
+                let (car, cdr) = self.store.fetch_cons(&rest)?;
+                self.rest = Some(*cdr);
+                Some(*car)
+            }
+            _ => {
+                self.improper_tail = Some(rest);
+                None
+            }
+        }
+    }
+}
+
+/// The result of `Store::hash_expr_partial`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PartialHash<F: LurkField> {
+    /// Every descendant was present, so the expression hashed.
+    Full(ScalarPtr<F>),
+    /// The dangling descendants that prevented hashing, in depth-first, left-to-right order.
+    Missing(Vec<Ptr<F>>),
+}
+
+/// The shape of an expression viewed as a list, returned by `Store::list_kind`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListKind {
+    /// A nil-terminated list with this many elements.
+    Proper(usize),
+    /// A cons whose cdr chain ends in something other than nil, e.g. `(1 . 2)`.
+    Improper,
+    /// Neither nil nor a cons.
+    Atom,
+}
+
+/// A hydrated, read-only `Store`, returned by `Store::freeze`. It offers no way to intern, so it can be shared by
+/// reference between threads: the hash caches it still fills are concurrent maps.
+#[derive(Debug)]
+pub struct FrozenStore<F: LurkField>(Store<F>);
+
+impl<F: LurkField> FrozenStore<F> {
+    pub fn fetch(&self, ptr: &Ptr<F>) -> Option<Expression<F>> {
+        self.0.fetch(ptr)
+    }
+
+    pub fn fetch_cont(&self, ptr: &ContPtr<F>) -> Option<Continuation<F>> {
+        self.0.fetch_cont(ptr)
+    }
+
+    pub fn hash_expr(&self, ptr: &Ptr<F>) -> Option<ScalarPtr<F>> {
+        self.0.hash_expr(ptr)
+    }
+
+    pub fn hash_cont(&self, ptr: &ContPtr<F>) -> Option<ScalarContPtr<F>> {
+        self.0.hash_cont(ptr)
+    }
+
+    pub fn fetch_scalar(&self, scalar_ptr: &ScalarPtr<F>) -> Option<Ptr<F>> {
+        self.0.fetch_scalar(scalar_ptr)
+    }
+
+    pub fn fetch_scalar_cont(&self, scalar_ptr: &ScalarContPtr<F>) -> Option<ContPtr<F>> {
+        self.0.fetch_scalar_cont(scalar_ptr)
+    }
+
+    /// Make the store mutable again.
+    pub fn thaw(self) -> Store<F> {
+        self.0
+    }
+}
+
+/// A store-independent expression tree of bounded depth, used to generate random stores and root pointers for
+/// property tests. Interning the same tree into two fresh stores yields identical stores, so generation is fully
+/// determined by proptest's seed.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArbitraryExpr {
+    Num(u64),
+    Sym(String),
+    Str(String),
+    Cons(Box<ArbitraryExpr>, Box<ArbitraryExpr>),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Arbitrary for ArbitraryExpr {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        let leaf = prop_oneof![
+            any::<u64>().prop_map(ArbitraryExpr::Num),
+            "[a-z][a-z0-9-]{0,8}".prop_map(ArbitraryExpr::Sym),
+            "[ -~]{0,12}".prop_map(ArbitraryExpr::Str),
+        ];
+        leaf.prop_recursive(4, 32, 2, |inner| {
+            (inner.clone(), inner)
+                .prop_map(|(car, cdr)| ArbitraryExpr::Cons(Box::new(car), Box::new(cdr)))
+        })
+        .boxed()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ArbitraryExpr {
+    /// Intern this tree into `store`, returning its root pointer.
+    pub fn intern<F: LurkField>(&self, store: &mut Store<F>) -> Ptr<F> {
+        match self {
+            Self::Num(n) => store.num(*n),
+            Self::Sym(name) => store.sym(name),
+            Self::Str(s) => store.str(s),
+            Self::Cons(car, cdr) => {
+                let car = car.intern(store);
+                let cdr = cdr.intern(store);
+                store.cons(car, cdr)
+            }
+        }
+    }
+
+    /// Build a fresh store containing this tree, returning the store and the tree's root pointer.
+    pub fn to_store<F: LurkField>(&self) -> (Store<F>, Ptr<F>) {
+        let mut store = Store::default();
+        let root = self.intern(&mut store);
+        (store, root)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ConstantPtrs<F: LurkField>(Option<ScalarPtr<F>>, Ptr<F>);
+
+impl<F: LurkField> ConstantPtrs<F> {
+    pub fn value(&self) -> F {
+        *self.scalar_ptr().value()
+    }
+    pub fn scalar_ptr(&self) -> ScalarPtr<F> {
+        self.0
+            .expect("ScalarPtr missing; hydrate_scalar_cache should have been called.")
+    }
+    pub const fn ptr(&self) -> Ptr<F> {
+        self.1
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NamedConstants<F: LurkField> {
+    pub t: ConstantPtrs<F>,
+    pub nil: ConstantPtrs<F>,
+    pub lambda: ConstantPtrs<F>,
+    pub quote: ConstantPtrs<F>,
+    pub let_: ConstantPtrs<F>,
+    pub letrec: ConstantPtrs<F>,
+    pub cons: ConstantPtrs<F>,
+    pub strcons: ConstantPtrs<F>,
+    pub begin: ConstantPtrs<F>,
+    pub car: ConstantPtrs<F>,
+    pub cdr: ConstantPtrs<F>,
+    pub atom: ConstantPtrs<F>,
+    pub emit: ConstantPtrs<F>,
+    pub sum: ConstantPtrs<F>,
+    pub diff: ConstantPtrs<F>,
+    pub product: ConstantPtrs<F>,
+    pub quotient: ConstantPtrs<F>,
+    pub modulo: ConstantPtrs<F>,
+    pub num_equal: ConstantPtrs<F>,
+    pub equal: ConstantPtrs<F>,
+    pub less: ConstantPtrs<F>,
+    pub less_equal: ConstantPtrs<F>,
+    pub greater: ConstantPtrs<F>,
+    pub greater_equal: ConstantPtrs<F>,
+    pub current_env: ConstantPtrs<F>,
+    pub if_: ConstantPtrs<F>,
+    pub hide: ConstantPtrs<F>,
+    pub commit: ConstantPtrs<F>,
+    pub num: ConstantPtrs<F>,
+    pub u64: ConstantPtrs<F>,
+    pub comm: ConstantPtrs<F>,
+    pub char: ConstantPtrs<F>,
+    pub eval: ConstantPtrs<F>,
+    pub open: ConstantPtrs<F>,
+    pub secret: ConstantPtrs<F>,
+    pub dummy: ConstantPtrs<F>,
+}
+
+impl<F: LurkField> NamedConstants<F> {
+    pub fn new(store: &Store<F>) -> Self {
+        let hash_sym = |name: &str| {
+            let ptr = store.get_lurk_sym(name, true).unwrap();
+            let maybe_scalar_ptr = store.hash_sym(ptr, HashScalar::Get);
+            ConstantPtrs(maybe_scalar_ptr, ptr)
+        };
+
+        let t = hash_sym("t");
+        let nil = ConstantPtrs(
+            Some(store.hash_nil(HashScalar::Get).unwrap()),
+            store.get_nil(),
+        );
+        let lambda = hash_sym("lambda");
+        let quote = hash_sym("quote");
         let let_ = hash_sym("let");
         let letrec = hash_sym("letrec");
         let cons = hash_sym("cons");
@@ -2764,6 +6370,30 @@ pub mod test {
     use libipld::serde::to_ipld;
     use libipld::Ipld;
 
+    proptest! {
+        #[test]
+        fn prop_arbitrary_expr_scalar_store_roundtrip(expr in any::<ArbitraryExpr>()) {
+            let (mut store, root) = expr.to_store::<Fr>();
+            store.hydrate_scalar_cache();
+
+            let (mut scalar_store, scalar_root) = ScalarStore::new_with_expr(&store, &root);
+            let scalar_root = scalar_root.unwrap();
+            let (mut store2, root2) = scalar_store.to_store_with_expr(&scalar_root).unwrap();
+            store2.hydrate_scalar_cache();
+
+            assert_eq!(Some(scalar_root), store2.hash_expr(&root2));
+            assert_eq!(root.tag(), root2.tag());
+        }
+
+        #[test]
+        fn prop_arbitrary_expr_hash_stable(expr in any::<ArbitraryExpr>()) {
+            let (store1, root1) = expr.to_store::<Fr>();
+            let (store2, root2) = expr.to_store::<Fr>();
+
+            assert_eq!(store1.hash_expr(&root1), store2.hash_expr(&root2));
+        }
+    }
+
     proptest! {
       #[test]
       fn test_scalar_ptr_ipld(x in any::<ScalarPtr<Fr>>())  {
@@ -3297,6 +6927,2682 @@ pub mod test {
         empty_str_fetch_aux(false);
     }
 
+    #[cfg(feature = "sha")]
+    #[test]
+    fn sha_hash_backend() {
+        use neptune::Poseidon;
+
+        let hash_cons = |store: &mut Store<Fr>| {
+            let (a, b) = (store.num(1), store.num(2));
+            let cons = store.cons(a, b);
+            let (a, b) = (store.hash_expr(&a).unwrap(), store.hash_expr(&b).unwrap());
+            let preimage = [a.tag_field(), *a.value(), b.tag_field(), *b.value()];
+            (store.hash_expr(&cons).unwrap(), preimage)
+        };
+
+        let (hash, preimage) = hash_cons(&mut Store::<Fr>::default());
+        let (hash2, _) = hash_cons(&mut Store::<Fr>::default());
+
+        // Internally consistent: identical structures hash identically across stores, to the SHA-256 reduction.
+        assert_eq!(hash, hash2);
+        assert_eq!(sha256_hash(&preimage), *hash.value());
+
+        // But different from what Poseidon would have produced.
+        let constants = HashConstants::<Fr>::default();
+        let poseidon = Poseidon::new_with_preimage(&preimage, constants.c4()).hash();
+        assert_ne!(poseidon, *hash.value());
+    }
+
+    #[test]
+    fn would_be_hashes() {
+        let mut store = Store::<Fr>::default();
+
+        let car = store.num(123);
+        let cdr = store.read("(a . \"b\")").unwrap();
+        let expected_cons = store.would_be_cons_hash(&car, &cdr).unwrap();
+        let cons_count = store.cons_store.len();
+
+        let arg = store.sym("x");
+        let env = empty_sym_env(&store);
+        let expected_fun = store.would_be_fun_hash(&arg, &cdr, &env).unwrap();
+        let fun_count = store.fun_store.len();
+
+        // Nothing was interned by computing the hashes.
+        assert_eq!(cons_count, store.cons_store.len());
+        assert_eq!(fun_count, store.fun_store.len());
+
+        let cons = store.intern_cons(car, cdr);
+        let fun = store.intern_fun(arg, cdr, env);
+        assert_eq!(Some(expected_cons), store.hash_expr(&cons));
+        assert_eq!(Some(expected_fun), store.hash_expr(&fun));
+    }
+
+    #[test]
+    fn hash_expression() {
+        let mut store = Store::<Fr>::default();
+
+        // The parent is not interned.
+        let car = store.num(1);
+        let cdr = store.read("(:b . \"c\")").unwrap();
+        let cons_count = store.cons_store.len();
+        let expected = store.hash_expression(&Expression::Cons(car, cdr)).unwrap();
+        assert_eq!(cons_count, store.cons_store.len());
+        let cons = store.intern_cons(car, cdr);
+        assert_eq!(Some(expected), store.hash_expr(&cons));
+
+        let nil = store.nil();
+        let sym = store.sym("sym");
+        let key = store.read(":key").unwrap();
+        let scalar = store.num(Num::Scalar(-Fr::from(1u64)));
+        let str = store.str("str");
+        let empty_str = store.str("");
+        let c = store.get_char('c');
+        let uint = store.uint64(12);
+        let secret = Fr::from(7u64);
+        let comm = store.hide(secret, cons);
+        let env = empty_sym_env(&store);
+        let fun = store.intern_fun(sym, cons, env);
+        let continuation = store.intern_cont_outermost();
+        let thunk = store.intern_thunk(Thunk {
+            value: cons,
+            continuation,
+        });
+
+        for ptr in [
+            cons, nil, sym, key, car, scalar, str, empty_str, c, uint, comm, fun, thunk,
+        ] {
+            let expr = store.fetch(&ptr).unwrap();
+            assert_eq!(store.hash_expr(&ptr), store.hash_expression(&expr));
+        }
+
+        // Symbols and strings must be interned.
+        let unknown = Sym::new(".UNKNOWN".into());
+        assert_eq!(None, store.hash_expression(&Expression::Sym(unknown)));
+        assert_eq!(None, store.hash_expression(&Expression::Str("unknown")));
+    }
+
+    #[test]
+    fn clear_caches_selectively() {
+        let mut store = Store::<Fr>::default();
+        let expr = store.read("(let ((a 1)) (cons a \"b\"))").unwrap();
+        let cont = store.intern_cont_outermost();
+        let expected = store.hash_expr(&expr).unwrap();
+        let expected_cont = store.hash_cont(&cont).unwrap();
+
+        store.clear_poseidon_cache();
+        assert!(store.poseidon_cache.is_empty());
+        assert!(!store.scalar_ptr_map.is_empty());
+        assert!(!store.scalar_ptr_cont_map.is_empty());
+        assert_eq!(Some(expr), store.fetch_scalar(&expected));
+
+        // Bypass the pointer cache so the hash is actually recomputed.
+        store.pointer_scalar_ptr_cache.clear();
+        assert_eq!(Some(expected), store.hash_expr(&expr));
+        assert!(!store.poseidon_cache.is_empty());
+
+        store.clear_reverse_maps();
+        assert!(store.scalar_ptr_map.is_empty());
+        assert!(store.scalar_ptr_cont_map.is_empty());
+        assert!(!store.poseidon_cache.is_empty());
+
+        assert_eq!(Some(expected), store.hash_expr(&expr));
+        assert_eq!(Some(expected_cont), store.hash_cont(&cont));
+        assert_eq!(Some(expr), store.fetch_scalar(&expected));
+    }
+
+    #[test]
+    fn cons_insertion_order() {
+        let mut store = Store::<Fr>::default();
+        let base = store.cons_store.len();
+
+        let (a, b, c) = (store.num(1), store.num(2), store.num(3));
+        let first = store.cons(a, b);
+        let second = store.cons(c, a);
+        let third = store.cons(b, c);
+        // Re-interning an existing cons does not add an entry.
+        store.cons(a, b);
+
+        assert_eq!(Some((a, b)), store.cons_at_index(base));
+        assert_eq!(Some((c, a)), store.cons_at_index(base + 1));
+        assert_eq!(Some((b, c)), store.cons_at_index(base + 2));
+        assert_eq!(None, store.cons_at_index(base + 3));
+
+        let indices = [first, second, third]
+            .iter()
+            .map(|p| p.1.idx())
+            .collect::<Vec<_>>();
+        let dumped = store
+            .cons_entries()
+            .skip(base)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![base, base + 1, base + 2], indices);
+        assert_eq!(indices, dumped);
+    }
+
+    #[test]
+    fn max_entries() {
+        let initial = Store::<Fr>::default().entry_count();
+        let mut store = Store::<Fr>::with_max_entries(initial + 3);
+
+        let a = store.try_intern_num(1).unwrap();
+        let b = store.try_intern_num(2).unwrap();
+        let cons = store.try_intern_cons(a, b).unwrap();
+        assert_eq!(initial + 3, store.entry_count());
+
+        // Existing entries can still be interned at capacity.
+        assert_eq!(Ok(a), store.try_intern_num(1));
+        assert_eq!(Ok(cons), store.try_intern_cons(a, b));
+
+        assert_eq!(
+            Err(StoreError::CapacityExceeded { limit: initial + 3 }),
+            store.try_intern_cons(b, a)
+        );
+        assert_eq!(
+            Err(StoreError::CapacityExceeded { limit: initial + 3 }),
+            store.try_intern_num(3)
+        );
+        assert_eq!(initial + 3, store.entry_count());
+    }
+
+    #[test]
+    fn env_extend_and_lookup() {
+        let mut store = Store::<Fr>::default();
+        let nil = empty_sym_env(&store);
+        let (x, y, z) = (store.sym("x"), store.sym("y"), store.sym("z"));
+        let (one, two, three) = (store.num(1), store.num(2), store.num(3));
+
+        assert_eq!(None, store.env_lookup(&nil, &x));
+
+        let env = store.env_extend(&nil, &x, &one);
+        let env = store.env_extend(&env, &y, &two);
+        assert_eq!(store.read("((y . 2) (x . 1))").unwrap(), env);
+        assert_eq!(Some(one), store.env_lookup(&env, &x));
+        assert_eq!(Some(two), store.env_lookup(&env, &y));
+        assert_eq!(None, store.env_lookup(&env, &z));
+
+        // The innermost binding shadows outer ones.
+        let shadowed = store.env_extend(&env, &x, &three);
+        assert_eq!(Some(three), store.env_lookup(&shadowed, &x));
+        assert_eq!(Some(one), store.env_lookup(&env, &x));
+    }
+
+    #[test]
+    fn export_import_symbols() {
+        let mut store1 = Store::<Fr>::default();
+        let syms = [
+            store1.sym("apple"),
+            store1.sym("banana"),
+            store1.key("cherry"),
+            store1.sym("lurk.apple"),
+        ];
+
+        let names = store1.export_symbols();
+        let mut store2 = Store::<Fr>::default();
+        store2.import_symbols(&names);
+        assert_eq!(names, store2.export_symbols());
+
+        let syms2 = [
+            store2.sym("apple"),
+            store2.sym("banana"),
+            store2.key("cherry"),
+            store2.sym("lurk.apple"),
+        ];
+        assert_eq!(syms, syms2);
+        assert_eq!(names, store2.export_symbols());
+    }
+
+    #[test]
+    fn sort_ptrs() {
+        let mut store = Store::<Fr>::default();
+        let minus_one = store.num(Num::Scalar(Fr::from(0u64) - Fr::from(1u64)));
+        let (one, three) = (store.num(1), store.num(3));
+        let (a, b) = (store.sym("a"), store.sym("b"));
+        let (str_a, str_z) = (store.str("a"), store.str("z"));
+        let nil = store.nil();
+
+        let mut ptrs = vec![b, three, str_z, minus_one, a, one, str_a, nil];
+        store.sort_ptrs(&mut ptrs);
+
+        // Nums compare as unsigned integers, so -1 (i.e. p - 1) is the largest.
+        assert_eq!(vec![nil, a, b, one, three, minus_one, str_a, str_z], ptrs);
+    }
+
+    #[test]
+    fn reserve_scalar_maps() {
+        let src = "(letrec ((f (lambda (x) (if (= x 0) \"done\" (f (- x 1)))))) (f 10))";
+        let mut store1 = Store::<Fr>::default();
+        let mut store2 = Store::<Fr>::default();
+        let expr1 = store1.read(src).unwrap();
+        let expr2 = store2.read(src).unwrap();
+        let outermost1 = store1.intern_cont_outermost();
+        let outermost2 = store2.intern_cont_outermost();
+        let cont1 = Continuation::Call0 {
+            saved_env: expr1,
+            continuation: outermost1,
+        }
+        .intern_aux(&mut store1);
+        let cont2 = Continuation::Call0 {
+            saved_env: expr2,
+            continuation: outermost2,
+        }
+        .intern_aux(&mut store2);
+
+        store1.reserve_scalar_maps();
+        assert!(store1.scalar_ptr_map.capacity() >= store1.cons_store.len());
+        assert!(store1.scalar_ptr_cont_map.capacity() >= store1.call0_store.len());
+        store1.hydrate_scalar_cache();
+
+        // Hydrate `store2` without reserving up front.
+        store2.ensure_constants();
+        for ptr in store2.dehydrated.clone() {
+            store2.hash_expr(&ptr).unwrap();
+        }
+        store2.hash_cont(&cont2).unwrap();
+
+        assert_eq!(store1.scalar_ptr_map.len(), store2.scalar_ptr_map.len());
+        for entry in store1.scalar_ptr_map.iter() {
+            assert_eq!(Some(*entry.value()), store2.fetch_scalar(entry.key()));
+        }
+        assert_eq!(store1.hash_cont(&cont1), store2.hash_cont(&cont2));
+        assert_eq!(
+            store1.scalar_ptr_cont_map.len(),
+            store2.scalar_ptr_cont_map.len()
+        );
+    }
+
+    #[test]
+    fn sym_name_cow() {
+        let mut store = Store::<Fr>::default();
+        let nil = store.nil();
+        let sym = store.sym("apple");
+        let key = store.key("apple");
+        let opaque = store.new_opaque_ptr();
+
+        assert_eq!(Some(Cow::Borrowed("LURK.NIL")), store.sym_name_cow(&nil));
+        assert!(matches!(
+            store.sym_name_cow(&sym),
+            Some(Cow::Borrowed(name)) if name.ends_with("APPLE")
+        ));
+        assert_eq!(store.sym_name_cow(&sym), store.sym_name_cow(&key));
+        assert_eq!(None, store.sym_name_cow(&opaque));
+    }
+
+    #[test]
+    fn print_improper_list() {
+        let mut store = Store::<Fr>::default();
+        let (one, two, three) = (store.num(1), store.num(2), store.num(3));
+
+        let list = store.intern_improper_list(&[one, two], three);
+        assert_eq!("(1 2 . 3)", list.fmt_to_string(&store));
+        assert_eq!(store.read("(1 2 . 3)").unwrap(), list);
+
+        let pair = store.cons(one, two);
+        let s = store.str("a");
+        let nested = store.intern_improper_list(&[pair, three], s);
+        assert_eq!("((1 . 2) 3 . \"a\")", nested.fmt_to_string(&store));
+
+        // A nil tail is just a proper list.
+        let nil = store.nil();
+        let proper = store.intern_improper_list(&[one, two], nil);
+        assert_eq!(store.list(&[one, two]), proper);
+        assert_eq!("(1 2)", proper.fmt_to_string(&store));
+    }
+
+    #[test]
+    fn poseidon_hash_utilities() {
+        let mut store = Store::<Fr>::default();
+        let (a, b, c) = (Fr::from(1u64), Fr::from(2u64), Fr::from(3u64));
+
+        let zero = Fr::from(0u64);
+        assert_eq!(
+            store.poseidon_hash2(a, b),
+            store.poseidon_hash4([zero, a, zero, b])
+        );
+        assert_eq!(
+            Store::<Fr>::with_pair_hash(PairHash::Arity3).poseidon_hash2(a, b),
+            store.poseidon_hash3(zero, a, b)
+        );
+        assert_ne!(store.poseidon_hash2(a, b), store.poseidon_hash2(b, a));
+
+        // The utilities reproduce the store's own cons and commitment hashes.
+        let (car, cdr) = (store.num(1), store.str("cdr"));
+        let cons = store.cons(car, cdr);
+        let (car_hash, cdr_hash) = (
+            store.hash_expr(&car).unwrap(),
+            store.hash_expr(&cdr).unwrap(),
+        );
+        let preimage = [
+            car_hash.tag_field(),
+            *car_hash.value(),
+            cdr_hash.tag_field(),
+            *cdr_hash.value(),
+        ];
+        assert_eq!(
+            *store.hash_expr(&cons).unwrap().value(),
+            store.poseidon_hash4(preimage)
+        );
+
+        let comm = store.hide(c, cons);
+        let cons_hash = store.hash_expr(&cons).unwrap();
+        assert_eq!(
+            *store.hash_expr(&comm).unwrap().value(),
+            store.poseidon_hash3(c, cons_hash.tag_field(), *cons_hash.value())
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "sha"))]
+    fn poseidon_hash_vectors() {
+        let store = Store::<Fr>::default();
+        let [a, b, c, d] = [1u64, 2, 3, 4].map(Fr::from);
+
+        assert_eq!(
+            "40c9847e75f83c2fe91d6f2fef0438e05ea0ef0d8ddae9999659f520244cb5fa",
+            store.poseidon_hash2(a, b).hex_digits()
+        );
+        assert_eq!(
+            "433c06860167dfc85b8d76f1a2193bf6632a83ec74c901cbb1166a19a0d8e4c9",
+            Store::<Fr>::with_pair_hash(PairHash::Arity3)
+                .poseidon_hash2(a, b)
+                .hex_digits()
+        );
+        assert_eq!(
+            "6ecf72b1497928802b48cf97b4df5d2a7514e5b5198504f3da49b58d1726d805",
+            store.poseidon_hash3(a, b, c).hex_digits()
+        );
+        assert_eq!(
+            "3d181224e2607dea961f35d9f769acb7cdefca33095ca2f3146437bcf428d9c5",
+            store.poseidon_hash4([a, b, c, d]).hex_digits()
+        );
+    }
+
+    #[test]
+    fn hide_and_open_list() {
+        let mut store = Store::<Fr>::default();
+        let secret = Fr::from(123u64);
+        let elts = [store.num(1), store.sym("a"), store.str("b")];
+        let list = store.list(&elts);
+        let printed = list.fmt_to_string(&store);
+
+        let comm = store.hide(secret, list);
+        let (opened_secret, opened) = store.open(comm).unwrap();
+        assert_eq!(secret, opened_secret);
+        assert_eq!(list, opened);
+        assert_eq!(printed, opened.fmt_to_string(&store));
+        assert_eq!(store.fetch(&list), store.fetch(&opened));
+
+        // Commitments can also be opened by their hash.
+        let comm_hash = *store.hash_expr(&comm).unwrap().value();
+        let comm_num = store.num(Num::Scalar(comm_hash));
+        assert_eq!(Some((secret, list)), store.open(comm_num));
+
+        // A payload known only by its hash opens to the same opaque pointer.
+        let opaque = store.intern_opaque_cons(Fr::from(42u64));
+        let opaque_comm = store.hide(secret, opaque);
+        let (_, opened) = store.open(opaque_comm).unwrap();
+        assert!(opened.is_opaque());
+        assert_eq!(opaque, opened);
+
+        // Unknown commitments don't open.
+        let unknown = store.num(Num::Scalar(Fr::from(987654321u64)));
+        assert_eq!(None, store.open(unknown));
+    }
+
+    #[test]
+    fn hash_char() {
+        let store = Store::<Fr>::default();
+        let dehydrated = store.dehydrated.len();
+        let a = store.get_char('A');
+        assert_eq!(Some('A'), store.fetch_char(&a));
+        assert_eq!(
+            ScalarPtr::from_parts(ExprTag::Char, Fr::from(65u64)),
+            store.hash_expr(&a).unwrap()
+        );
+        // Chars are immediate, so there is nothing to hydrate.
+        assert_eq!(dehydrated, store.dehydrated.len());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "hash collision")]
+    fn scalar_ptr_collision_detected() {
+        let mut store = Store::<Fr>::default();
+        let (one, two, nil) = (store.num(1), store.num(2), store.nil());
+        let a = store.intern_cons(one, nil);
+        let b = store.intern_cons(two, nil);
+        let hash = Fr::from(123u64);
+
+        // Stub a collision by hashing two different conses to the same value.
+        store.create_scalar_ptr(a, hash);
+        store.create_scalar_ptr(b, hash);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "hash collision")]
+    fn scalar_cont_ptr_collision_detected() {
+        let mut store = Store::<Fr>::default();
+        let (outermost, terminal) = (store.intern_cont_outermost(), store.intern_cont_terminal());
+        let a = Continuation::Emit {
+            continuation: outermost,
+        }
+        .intern_aux(&mut store);
+        let b = Continuation::Emit {
+            continuation: terminal,
+        }
+        .intern_aux(&mut store);
+        let hash = Fr::from(123u64);
+
+        store.create_cont_scalar_ptr(a, hash);
+        store.create_cont_scalar_ptr(b, hash);
+    }
+
+    #[test]
+    fn opaque_scalar_ptr_is_not_a_collision() {
+        let mut store = Store::<Fr>::default();
+        let (one, nil) = (store.num(1), store.nil());
+        let cons = store.intern_cons(one, nil);
+        let hash = *store.hash_expr(&cons).unwrap().value();
+        let opaque = store.intern_opaque_cons(hash);
+
+        store.create_scalar_ptr(opaque, hash);
+        store.create_scalar_ptr(cons, hash);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "hash collision")]
+    fn arena_scalar_ptr_collision_detected() {
+        let mut store = Store::<Fr>::with_cons_arena();
+        let (one, two, nil) = (store.num(1), store.num(2), store.nil());
+        let a = store.intern_cons(one, nil);
+        let b = store.intern_cons(two, nil);
+        let hash = Fr::from(123u64);
+
+        store.create_scalar_ptr(a, hash);
+        store.create_scalar_ptr(b, hash);
+    }
+
+    #[test]
+    fn arena_copies_are_not_a_collision() {
+        let mut store = Store::<Fr>::with_cons_arena();
+        let (one, nil) = (store.num(1), store.nil());
+        let inner = [store.intern_cons(one, nil), store.intern_cons(one, nil)];
+        let outer = inner.map(|cons| store.intern_cons(cons, nil));
+        assert_ne!(outer[0], outer[1]);
+        let x = store.sym("x");
+        let funs = outer.map(|body| store.intern_fun(x, body, nil));
+        let outermost = store.intern_cont_outermost();
+        let conts = outer.map(|saved_env| {
+            store
+                .try_intern_cont(Continuation::Lookup {
+                    saved_env,
+                    continuation: outermost,
+                })
+                .unwrap()
+        });
+        assert_ne!(conts[0], conts[1]);
+
+        assert_eq!(store.hash_expr(&outer[0]), store.hash_expr(&outer[1]));
+        assert_eq!(store.hash_expr(&funs[0]), store.hash_expr(&funs[1]));
+        assert_eq!(store.hash_cont(&conts[0]), store.hash_cont(&conts[1]));
+    }
+
+    #[test]
+    fn hash_thunk_value() {
+        let mut store = Store::<Fr>::default();
+        let value = store.num(123);
+        let continuation = store.intern_cont_outermost();
+
+        let direct = store.hash_thunk_value(&value, &continuation).unwrap();
+        assert_eq!(0, store.thunk_store.len());
+
+        let thunk = store.intern_thunk(Thunk {
+            value,
+            continuation,
+        });
+        assert_eq!(direct, store.hash_expr(&thunk).unwrap());
+    }
+
+    #[test]
+    fn list_iter() {
+        let mut store = Store::<Fr>::default();
+        let n = 10_000;
+        let list = (0..n).rev().fold(store.nil(), |acc, i| {
+            let num = store.num(i);
+            store.intern_cons(num, acc)
+        });
+
+        let mut iter = store.list_iter(list);
+        let mut expected = 0;
+        for elt in iter.by_ref() {
+            assert_eq!(Some(&Num::U64(expected)), store.fetch_num(&elt));
+            expected += 1;
+        }
+        assert_eq!(n, expected);
+        assert_eq!(None, iter.improper_tail());
+
+        let (one, two, three) = (store.num(1), store.num(2), store.num(3));
+        let improper = store.intern_improper_list(&[one, two], three);
+        let mut iter = store.list_iter(improper);
+        assert_eq!(vec![one, two], iter.by_ref().collect::<Vec<_>>());
+        assert_eq!(Some(three), iter.improper_tail());
+    }
+
+    #[test]
+    fn hash_components() {
+        let mut store = Store::<Fr>::default();
+        let outermost = store.intern_cont_outermost();
+        let cont = Continuation::Emit {
+            continuation: outermost,
+        }
+        .intern_aux(&mut store);
+        let components = store.get_hash_components_cont(&cont).unwrap();
+        assert_eq!(
+            *store.hash_cont(&cont).unwrap().value(),
+            store.hash_cont_components(&components)
+        );
+
+        let (one, nil) = (store.num(1), store.nil());
+        let cons = store.intern_cons(one, nil);
+        let [car, cdr] =
+            [one, nil].map(|ptr| store.hash_expr(&ptr).unwrap().into_hash_components());
+        assert_eq!(
+            *store.hash_expr(&cons).unwrap().value(),
+            store.hash_expr_components(&[car[0], car[1], cdr[0], cdr[1]])
+        );
+
+        let thunk = store.intern_thunk(Thunk {
+            value: cons,
+            continuation: cont,
+        });
+        let components = store
+            .get_hash_components_thunk(store.fetch_thunk(&thunk).unwrap())
+            .unwrap();
+        assert_eq!(
+            *store.hash_expr(&thunk).unwrap().value(),
+            store.hash_expr_components(&components)
+        );
+    }
+
+    #[test]
+    fn rename_sym() {
+        let mut store = Store::<Fr>::default();
+        let apple = store.sym("apple");
+        let nil = store.nil();
+        let cons = store.intern_cons(apple, nil);
+        let before = store.hash_expr(&cons).unwrap();
+
+        let old_name = store.sym_name_cow(&apple).unwrap().into_owned();
+        let new_name = old_name.replace("APPLE", "BANANA");
+        store.rename_sym(&old_name, &new_name).unwrap();
+
+        let (car, _) = store.car_cdr(&cons).unwrap();
+        assert_eq!(apple, car);
+        assert_eq!(Some(new_name.as_str()), store.sym_name_cow(&car).as_deref());
+        assert_eq!(apple, store.sym("banana"));
+
+        let after = store.hash_expr(&cons).unwrap();
+        assert_ne!(before, after);
+        let mut fresh = Store::<Fr>::default();
+        let (banana, nil) = (fresh.sym("banana"), fresh.nil());
+        let fresh_cons = fresh.intern_cons(banana, nil);
+        assert_eq!(fresh.hash_expr(&fresh_cons).unwrap(), after);
+        assert_eq!(fresh_cons.fmt_to_string(&fresh), cons.fmt_to_string(&store));
+
+        store.hydrate_scalar_cache();
+        assert_eq!(Some(cons), store.fetch_scalar(&after));
+
+        // Continuations reaching the symbol are rehashed too.
+        let outermost = store.intern_cont_outermost();
+        let one = store.num(1);
+        let binding = store.cons(apple, one);
+        let env = store.cons(binding, nil);
+        let lookup = store
+            .try_intern_cont(Continuation::Lookup {
+                saved_env: env,
+                continuation: outermost,
+            })
+            .unwrap();
+        let cont_before = store.hash_cont(&lookup).unwrap();
+        let cherry_name = old_name.replace("APPLE", "CHERRY");
+        store.rename_sym(&new_name, &cherry_name).unwrap();
+        assert_eq!(None, store.fetch_scalar_cont(&cont_before));
+        store.hydrate_scalar_cache();
+        let cont_after = store.hash_cont(&lookup).unwrap();
+        assert_ne!(cont_before, cont_after);
+        assert_eq!(Some(lookup), store.fetch_scalar_cont(&cont_after));
+
+        // The old names no longer resolve, and interning one creates a new symbol.
+        assert!(!store.contains_sym(&old_name));
+        assert!(!store.contains_sym(&new_name));
+        let new_apple = store.sym("apple");
+        assert_ne!(apple, new_apple);
+        assert_eq!(
+            Some(old_name.as_str()),
+            store.sym_name_cow(&new_apple).as_deref()
+        );
+        assert_eq!(new_apple, store.sym("apple"));
+        assert_eq!(apple, store.sym("cherry"));
+        store.rename_sym(&old_name, &new_name).unwrap();
+        assert_eq!(new_apple, store.sym("banana"));
+
+        assert_eq!(
+            Err(StoreError::UnknownSymbol("NOPE".into())),
+            store.rename_sym("NOPE", "YEP")
+        );
+        let date = store.sym("date");
+        let date_name = store.sym_name_cow(&date).unwrap().into_owned();
+        assert_eq!(
+            Err(StoreError::SymbolExists(new_name.clone())),
+            store.rename_sym(&date_name, &new_name)
+        );
+    }
+
+    #[test]
+    fn gc_stats() {
+        let mut store = Store::<Fr>::default();
+        let baseline: HashMap<_, _> = Store::<Fr>::default()
+            .substore_sizes()
+            .iter()
+            .map(|(name, len, _)| (*name, *len))
+            .collect();
+
+        let nums: Vec<_> = (0..10u64).map(|i| store.num(i)).collect();
+        let live = store.list(&nums[..3]);
+        // Seven more conses, and seven nums only they reference.
+        let _garbage = store.list(&nums[3..]);
+        let (x, s, nil) = (store.sym("x"), store.str("garbage"), store.nil());
+        let _garbage_fun = store.intern_fun(x, s, nil);
+
+        let mut roots = [live];
+        let printed = live.fmt_to_string(&store);
+        let bytes = store.estimated_bytes();
+        let stats = store.gc(&mut roots);
+
+        assert_eq!(printed, roots[0].fmt_to_string(&store));
+        let expect = |name: &str, live: usize, freed: usize| {
+            assert_eq!(
+                SubstoreGcStats {
+                    live: baseline[name] + live,
+                    freed
+                },
+                stats.substores[name],
+                "{name}"
+            );
+        };
+        expect("cons_store", 3, 7);
+        expect("num_store", 3, 7);
+        expect("fun_store", 0, 1);
+        assert_eq!(baseline["sym_store"], stats.substores["sym_store"].live);
+        assert!(stats.substores["sym_store"].freed > 0);
+        assert!(stats.substores["str_store"].freed > 0);
+        assert_eq!(bytes - store.estimated_bytes(), stats.bytes_reclaimed);
+
+        // Collecting again frees nothing.
+        assert_eq!(0, store.gc(&mut roots).freed());
+    }
+
+    #[test]
+    fn frozen_store_concurrent_reads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FrozenStore<Fr>>();
+
+        let mut store = Store::<Fr>::default();
+        let lists: Vec<_> = (0..8u64)
+            .map(|i| {
+                let elts: Vec<_> = (0..100).map(|j| store.num(i * 100 + j)).collect();
+                store.list(&elts)
+            })
+            .collect();
+        let printed: Vec<_> = lists.iter().map(|l| l.fmt_to_string(&store)).collect();
+
+        let frozen = store.freeze();
+        std::thread::scope(|scope| {
+            for (list, printed) in lists.iter().zip(&printed) {
+                let frozen = &frozen;
+                scope.spawn(move || {
+                    let scalar_ptr = frozen.hash_expr(list).unwrap();
+                    assert_eq!(Some(*list), frozen.fetch_scalar(&scalar_ptr));
+                    assert!(matches!(frozen.fetch(list), Some(Expression::Cons(..))));
+                    assert_eq!(*printed, list.fmt_to_string(&frozen.0));
+                });
+            }
+        });
+
+        let mut store = frozen.thaw();
+        store.num(12345);
+    }
+
+    #[test]
+    fn canonicalize() {
+        // The `LightData` serialization of the scalar pointers of every substore's entries, in index order.
+        fn ser(store: &Store<Fr>) -> Vec<u8> {
+            let expr = |tag, idx| store.hash_expr(&Ptr(tag, RawPtr::new(idx))).unwrap();
+            let substore = |tag, len| (0..len).map(|idx| expr(tag, idx)).collect::<Vec<_>>();
+            let interned = |strings: &StringSet, tag| {
+                strings
+                    .0
+                    .into_iter()
+                    .map(|(symbol, _)| expr(tag, symbol.to_usize()))
+                    .collect::<Vec<_>>()
+            };
+            let exprs = vec![
+                substore(ExprTag::Cons, store.cons_store.len()),
+                substore(ExprTag::Comm, store.comm_store.len()),
+                substore(ExprTag::Fun, store.fun_store.len()),
+                substore(ExprTag::Num, store.num_store.len()),
+                substore(ExprTag::Thunk, store.thunk_store.len()),
+                interned(&store.sym_store, ExprTag::Sym),
+                interned(&store.str_store, ExprTag::Str),
+            ];
+            let emits: Vec<ScalarContPtr<Fr>> = (0..store.emit_store.len())
+                .map(|idx| {
+                    let ptr = ContPtr(ContTag::Emit, RawPtr::new(idx));
+                    store.hash_cont(&ptr).unwrap()
+                })
+                .collect();
+            (exprs, emits).ser().ser()
+        }
+
+        let mut a = Store::<Fr>::default();
+        let mut b = Store::<Fr>::default();
+
+        let (one, two, nil) = (a.num(1), a.num(2), a.nil());
+        let x = a.str("x");
+        let list = a.list(&[one, two, x]);
+        let y = a.sym("y");
+        let _pair = a.cons(y, list);
+        let _fun = a.intern_fun(y, list, nil);
+        let _comm = a.hide(Fr::from(7u64), list);
+
+        // The same entries, interned in a different order.
+        let y = b.sym("y");
+        let x = b.str("x");
+        let (two, one, nil) = (b.num(2), b.num(1), b.nil());
+        let list = b.list(&[one, two, x]);
+        let _comm = b.hide(Fr::from(7u64), list);
+        let _fun = b.intern_fun(y, list, nil);
+        let _pair = b.cons(y, list);
+
+        assert_ne!(ser(&a), ser(&b));
+        let (a, b) = (a.canonicalize(), b.canonicalize());
+        assert_eq!(ser(&a), ser(&b));
+        assert_eq!(ser(&a), ser(&a.canonicalize()));
+    }
+
+    #[test]
+    fn cache_sizes() {
+        let mut store = Store::<Fr>::default();
+        store.hydrate_scalar_cache();
+        let (a4, a6, _) = store.cache_sizes();
+        let (exprs, _) = store.reverse_map_sizes();
+
+        let (one, two) = (store.num(1), store.num(2));
+        let cons = store.cons(one, two);
+        store.hash_expr(&cons);
+        assert_eq!((a4 + 1, a6), (store.cache_sizes().0, store.cache_sizes().1));
+        assert!(store.reverse_map_sizes().0 > exprs);
+
+        let x = store.sym("x");
+        let fun = store.intern_fun(x, cons, cons);
+        store.hash_expr(&fun);
+        assert_eq!(a6 + 1, store.cache_sizes().1);
+    }
+
+    #[test]
+    fn hash_num_value() {
+        let mut store = Store::<Fr>::default();
+        let (exprs, _) = store.reverse_map_sizes();
+        let nums = [
+            Num::U64(123),
+            Num::Scalar(Fr::from(123u64) - Fr::from(456u64)),
+        ];
+
+        let direct: Vec<_> = nums.iter().map(|n| store.hash_num_value(n)).collect();
+        assert_eq!(exprs, store.reverse_map_sizes().0);
+        assert!(store.get_num(nums[0]).is_none());
+
+        for (n, direct) in nums.into_iter().zip(direct) {
+            let ptr = store.intern_num(n);
+            assert_eq!(direct, store.hash_expr(&ptr).unwrap());
+        }
+    }
+
+    #[test]
+    fn string_car_cdr() {
+        let mut store = Store::<Fr>::default();
+        let s = store.str("héllo");
+        let (car, cdr) = store.string_car_cdr(&s).unwrap();
+        assert_eq!(store.get_char('h'), car);
+        assert_eq!(Some("éllo"), store.fetch_str(&cdr));
+        let (car, cdr) = store.string_car_cdr(&cdr).unwrap();
+        assert_eq!(store.get_char('é'), car);
+        assert_eq!(Some("llo"), store.fetch_str(&cdr));
+        assert_eq!(
+            store.car_cdr(&s).unwrap(),
+            store.string_car_cdr(&s).unwrap()
+        );
+
+        let empty = store.str("");
+        let nil = store.nil();
+        assert_eq!(Some((nil, empty)), store.string_car_cdr(&empty));
+
+        let num = store.num(1);
+        assert_eq!(None, store.string_car_cdr(&num));
+        assert_eq!(None, store.string_car_cdr(&nil));
+    }
+
+    #[test]
+    fn intern_static_sym() {
+        let mut store = Store::<Fr>::default();
+        let entries = store.entry_count();
+
+        for wellknown in WellKnown::ALL {
+            let ptr = store.intern_static_sym(wellknown.full_name());
+            assert_eq!(store.lurk_sym(wellknown.name()), ptr);
+            assert_eq!(Some(wellknown), store.is_wellknown_sym(&ptr));
+        }
+        assert_eq!(entries, store.entry_count());
+    }
+
+    #[test]
+    fn reachable_scalar_ptrs_topo() {
+        let mut store = Store::<Fr>::default();
+        let (a, b) = (store.num(1), store.sym("b"));
+        let cons = store.cons(a, b);
+        let h = |store: &Store<Fr>, ptr| store.hash_expr(&ptr).unwrap();
+
+        assert_eq!(
+            vec![h(&store, a), h(&store, b), h(&store, cons)],
+            store.reachable_scalar_ptrs_topo(&cons)
+        );
+
+        // Shared children are listed once, before every parent.
+        let outer = store.cons(cons, cons);
+        let order = store.reachable_scalar_ptrs_topo(&outer);
+        assert_eq!(
+            vec![
+                h(&store, a),
+                h(&store, b),
+                h(&store, cons),
+                h(&store, outer)
+            ],
+            order
+        );
+    }
+
+    #[test]
+    fn nil_as_symbol() {
+        let mut store = Store::<Fr>::default();
+        let nil = store.nil();
+        assert_eq!(ExprTag::Nil, nil.tag());
+        assert_eq!(nil, store.get_nil());
+        assert_eq!(nil, store.lurk_sym("nil"));
+        assert_eq!(nil, store.read("nil").unwrap());
+        assert_eq!(nil, store.list(&[]));
+        assert!(!store.is_nil_symbol(&nil));
+        let sym = store.sym("nil");
+        assert_eq!(nil, sym);
+        assert!(!store.is_nil_symbol(&sym));
+        assert_eq!(Some(Expression::Nil), store.fetch(&sym));
+        assert_eq!(Some(Sym::new(".LURK.NIL".into())), store.fetch_sym(&nil));
+
+        let mut store = Store::<Fr>::with_nil_as_symbol(true);
+        let nil = store.nil();
+        assert_eq!(ExprTag::Nil, nil.tag());
+        assert_eq!(nil, store.get_nil());
+        assert_eq!(nil, store.list(&[]));
+        let one = store.num(1);
+        let list = store.list(&[one]);
+        assert_eq!((one, nil), store.car_cdr(&list).unwrap());
+
+        let sym = store.lurk_sym("nil");
+        assert_eq!(ExprTag::Sym, sym.tag());
+        assert_eq!(sym, store.read("nil").unwrap());
+        assert_ne!(store.hash_expr(&nil), store.hash_expr(&sym));
+        assert!(store.is_nil_symbol(&sym));
+        assert!(!store.is_nil_symbol(&nil));
+        assert_eq!(sym, store.sym("nil"));
+        assert_eq!(
+            Some(Expression::Sym(Sym::new(".LURK.NIL".into()))),
+            store.fetch(&sym)
+        );
+    }
+
+    #[test]
+    fn try_intern_u64_checked() {
+        let mut store = Store::<Fr>::default();
+
+        let ptr = store
+            .try_intern_u64_checked(Num::Scalar(Fr::from(123u64)))
+            .unwrap();
+        assert_eq!(store.uint64(123), ptr);
+        assert_eq!(Some(UInt::U64(123)), store.fetch_uint(&ptr));
+
+        let max = store.try_intern_u64_checked(u64::MAX).unwrap();
+        assert_eq!(store.uint64(u64::MAX), max);
+
+        let too_big = Fr::from(u64::MAX) + Fr::from(1u64);
+        assert!(matches!(
+            store.try_intern_u64_checked(Num::Scalar(too_big)),
+            Err(StoreError::OutOfRange(_))
+        ));
+        assert!(matches!(
+            store.try_intern_u64_checked(Num::Scalar(-Fr::from(1u64))),
+            Err(StoreError::OutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn intern_closure() {
+        let mut store = Store::<Fr>::default();
+        let arg = store.sym("x");
+        let body = store.read("(+ x y)").unwrap();
+        let empty = empty_sym_env(&store);
+        let env1 = store.read("((y . 1))").unwrap();
+        let env2 = store.read("((y . 2))").unwrap();
+
+        let template = store.intern_fun(arg, body, empty);
+        let closure1 = store.intern_closure(&template, &env1).unwrap();
+        let closure2 = store.intern_closure(&template, &env2).unwrap();
+        assert_ne!(closure1, closure2);
+        assert_eq!(closure1, store.intern_fun(arg, body, env1));
+
+        let (_, body1, closed_env1) = *store.fetch_fun(&closure1).unwrap();
+        let (_, body2, closed_env2) = *store.fetch_fun(&closure2).unwrap();
+        assert_eq!(body1.1, body2.1);
+        assert_eq!(env1, closed_env1);
+        assert_eq!(env2, closed_env2);
+
+        assert_eq!(None, store.intern_closure(&body, &env1));
+    }
+
+    #[test]
+    fn simple_cont_constants() {
+        let mut store = Store::<Fr>::default();
+        let simple = [
+            (Continuation::Outermost, store.get_cont_outermost()),
+            (Continuation::Error, store.get_cont_error()),
+            (Continuation::Terminal, store.get_cont_terminal()),
+            (Continuation::Dummy, store.get_cont_dummy()),
+        ];
+        for (continuation, ptr) in &simple {
+            assert_eq!(continuation.get_simple_cont(), *ptr);
+            assert_eq!(continuation.cont_tag(), ptr.0);
+        }
+
+        let interned = [
+            store.intern_cont_outermost(),
+            store.intern_cont_error(),
+            store.intern_cont_terminal(),
+            store.intern_cont_dummy(),
+        ];
+        store.hydrate_scalar_cache();
+        for ((_, ptr), interned) in simple.iter().zip(interned) {
+            assert_eq!(*ptr, interned);
+            assert!(store.hash_cont(ptr).is_some());
+        }
+        assert_eq!(ContPtr::OUTERMOST, store.get_cont_outermost());
+        assert_eq!(
+            ContPtr::TERMINAL,
+            Store::<Fr>::default().get_cont_terminal()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn json_round_trip() {
+        let mut store = Store::<Fr>::default();
+        let expr = store
+            .read("(let ((x 0x10) (y \"str\")) (cons :key (quote (#\\a nil 123u64 . -1))))")
+            .unwrap();
+        let secret = Fr::from(42u64);
+        let comm = store.hide(secret, expr);
+        let arg = store.sym("x");
+        let nil = store.nil();
+        let fun = store.intern_fun(arg, comm, nil);
+
+        let json = store.to_json(&fun);
+        let str_ptr = store.read("\"str\"").unwrap();
+        assert_eq!(serde_json::json!({ "str": "str" }), store.to_json(&str_ptr));
+
+        let mut other = Store::<Fr>::default();
+        let ptr = other.from_json(&json).unwrap();
+        assert_eq!(store.hash_expr(&fun), other.hash_expr(&ptr));
+        assert_eq!(json, other.to_json(&ptr));
+        assert_eq!(fun, store.from_json(&json).unwrap());
+
+        for bad in [
+            serde_json::json!({ "cons": [{ "num": "0x1" }] }),
+            serde_json::json!({ "num": "12" }),
+            serde_json::json!({ "sym": "FOO" }),
+            serde_json::json!({ "thunk": null }),
+            serde_json::json!(null),
+        ] {
+            assert!(matches!(
+                other.from_json(&bad),
+                Err(StoreError::InvalidJson(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn ptr_equal_ci() {
+        let mut store = Store::<Fr>::default();
+        store.set_case_mode(CaseMode::Preserve);
+
+        let a = store.read("(Foo 1 (:Bar . \"s\"))").unwrap();
+        let b = store.read("(foo 1 (:bar . \"s\"))").unwrap();
+        let c = store.read("(foo 1 (:bar . \"S\"))").unwrap();
+        let d = store.read("(foo 2 (:bar . \"s\"))").unwrap();
+        let e = store.read("(foo 1 (bar . \"s\"))").unwrap();
+        store.hydrate_scalar_cache();
+
+        assert_ne!(a, b);
+        assert!(!store.ptr_eq(&a, &b).unwrap());
+        assert!(store.ptr_equal_ci(&a, &b));
+        assert!(store.ptr_equal_ci(&b, &a));
+        assert!(store.ptr_equal_ci(&a, &a));
+
+        // Only symbol names are folded.
+        assert!(!store.ptr_equal_ci(&a, &c));
+        assert!(!store.ptr_equal_ci(&a, &d));
+        assert!(!store.ptr_equal_ci(&a, &e));
+    }
+
+    #[test]
+    fn interner_bytes() {
+        let mut store = Store::<Fr>::default();
+        let (sym_bytes, str_bytes) = store.interner_bytes();
+        assert!(sym_bytes > 0);
+        let total = store.estimated_bytes();
+
+        let long = "x".repeat(1000);
+        store.str(&long);
+        let (sym_bytes_after, str_bytes_after) = store.interner_bytes();
+        assert_eq!(sym_bytes, sym_bytes_after);
+        // The whole string and its two-byte length prefix, plus all of its interned suffixes.
+        assert!(str_bytes_after >= str_bytes + long.len() + 2);
+        assert_eq!(total + str_bytes_after - str_bytes, store.estimated_bytes());
+
+        store.sym("a-new-symbol");
+        assert!(store.interner_bytes().0 > sym_bytes);
+    }
+
+    #[test]
+    fn intern_num_from_bytes() {
+        let mut store = Store::<Fr>::default();
+        let p_minus_1 = -Fr::from(1u64);
+        let bytes = p_minus_1.to_bytes();
+
+        let ptr = store.intern_num_from_bytes(&bytes).unwrap();
+        assert_eq!(Some(&Num::Scalar(p_minus_1)), store.fetch_num(&ptr));
+        assert_eq!(p_minus_1, *store.hash_expr(&ptr).unwrap().value());
+
+        let small = store
+            .intern_num_from_bytes(&Fr::from(7u64).to_bytes())
+            .unwrap();
+        assert_eq!(store.num(7), small);
+
+        // p itself is not canonical.
+        let mut p = bytes.clone();
+        p[0] += 1;
+        assert!(matches!(
+            store.intern_num_from_bytes(&p),
+            Err(StoreError::NonCanonicalBytes)
+        ));
+        assert!(matches!(
+            store.intern_num_from_bytes(&bytes[1..]),
+            Err(StoreError::NonCanonicalBytes)
+        ));
+    }
+
+    #[test]
+    fn is_dangling() {
+        let mut store = Store::<Fr>::default();
+        let cons = store.read("(1 . \"two\")").unwrap();
+        let sym = store.sym("sym");
+        let opaque = store.intern_opaque_cons(Fr::from(123u64));
+        for ptr in [cons, sym, store.get_nil(), store.get_char('c'), opaque] {
+            assert!(!store.is_dangling(&ptr));
+        }
+
+        let past_end = Ptr(ExprTag::Cons, RawPtr::new(store.cons_store.len()));
+        assert!(store.is_dangling(&past_end));
+        assert_eq!(None, store.fetch(&past_end));
+        let num = Ptr(ExprTag::Num, RawPtr::new(store.num_store.len() + 10));
+        assert!(store.is_dangling(&num));
+        let str = Ptr(ExprTag::Str, RawPtr::new(usize::MAX >> 1));
+        assert!(store.is_dangling(&str));
+    }
+
+    #[test]
+    fn pair_hash_arity3() {
+        let hash_cons = |store: &mut Store<Fr>| {
+            let cons = store.read("(a . \"b\")").unwrap();
+            store.hash_expr(&cons).unwrap()
+        };
+
+        let mut store = Store::<Fr>::with_pair_hash(PairHash::Arity3);
+        let hash = hash_cons(&mut store);
+        assert_eq!(
+            hash,
+            hash_cons(&mut Store::<Fr>::with_pair_hash(PairHash::Arity3))
+        );
+        assert_ne!(hash, hash_cons(&mut Store::<Fr>::default()));
+        assert_eq!(
+            hash_cons(&mut Store::<Fr>::default()),
+            hash_cons(&mut Store::<Fr>::with_pair_hash(PairHash::Arity4))
+        );
+
+        let a = store.sym("a");
+        let b = store.str("b");
+        let (a, b) = (store.hash_expr(&a).unwrap(), store.hash_expr(&b).unwrap());
+        let tags = a.tag_field() + b.tag_field() * Fr::from(1 << 16);
+        assert_eq!(
+            *hash.value(),
+            store.poseidon_hash3(tags, *a.value(), *b.value())
+        );
+
+        // The store's own symbols were hashed with the same construction.
+        store.hydrate_scalar_cache();
+        let nil = store.nil();
+        let nil_hash = store.hash_expr(&nil).unwrap();
+        assert_eq!(Some(nil), store.fetch_scalar(&nil_hash));
+        assert_ne!(
+            nil_hash,
+            Store::<Fr>::default().hash_expr(&store.get_nil()).unwrap()
+        );
+    }
+
+    #[test]
+    fn contains_sym() {
+        let mut store = Store::<Fr>::default();
+        let foo = store.sym("foo");
+        let long = "a-symbol-whose-name-does-not-fit-in-the-stack-buffer-used-by-contains-sym";
+        let long_sym = store.sym(long);
+
+        for (name, ptr) in [("foo", foo), (long, long_sym)] {
+            let sym = store.fetch_sym(&ptr).unwrap();
+            assert!(store.get_sym::<&str>(sym).is_some());
+            assert!(store.contains_sym(name));
+            assert!(store.contains_sym(&name.to_ascii_uppercase()));
+        }
+        assert!(store.contains_sym("Foo"));
+        assert!(store.contains_sym("lurk.lambda"));
+
+        let mut other = Store::<Fr>::default();
+        let absent = other.sym("absent");
+        let absent = other.fetch_sym(&absent).unwrap();
+        assert!(store.get_sym::<&str>(absent).is_none());
+        assert!(!store.contains_sym("absent"));
+        assert!(!store.contains_sym(&long[1..]));
+
+        store.set_case_mode(CaseMode::Preserve);
+        store.read("Mixed").unwrap();
+        assert!(store.contains_sym("Mixed"));
+        assert!(!store.contains_sym("MIXED"));
+        assert!(!store.contains_sym("mixed"));
+    }
+
+    #[test]
+    fn try_intern_cont() {
+        let mut store = Store::<Fr>::default();
+        let env = store.read("((x . 1))").unwrap();
+        let args = store.read("(1 2)").unwrap();
+        let num = store.num(1);
+        let outermost = store.intern_cont_outermost();
+
+        let binop = store
+            .try_intern_cont_binop(Op2::Sum, env, args, outermost)
+            .unwrap();
+        assert_eq!(
+            binop,
+            Continuation::Binop {
+                operator: Op2::Sum,
+                saved_env: env,
+                unevaled_args: args,
+                continuation: outermost,
+            }
+            .intern_aux(&mut store)
+        );
+        let nil = store.nil();
+        store
+            .try_intern_cont_binop(Op2::Sum, nil, nil, binop)
+            .unwrap();
+
+        assert!(matches!(
+            store.try_intern_cont_binop(Op2::Sum, num, args, outermost),
+            Err(StoreError::InvalidContinuationField {
+                field: "saved_env",
+                ..
+            })
+        ));
+        assert!(matches!(
+            store.try_intern_cont(Continuation::Let {
+                var: num,
+                body: args,
+                saved_env: env,
+                continuation: outermost,
+            }),
+            Err(StoreError::InvalidContinuationField { field: "var", .. })
+        ));
+
+        let dangling = ContPtr(ContTag::Binop, RawPtr::new(store.binop_store.len()));
+        assert!(matches!(
+            store.try_intern_cont(Continuation::Emit {
+                continuation: dangling
+            }),
+            Err(StoreError::InvalidContinuationField {
+                field: "continuation",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn intern_list_from_iter() {
+        let mut store = Store::<Fr>::default();
+        let elts: Vec<_> = (0..1000u64).map(|n| store.get_u64(n)).collect();
+        let expected = store.intern_list(&elts);
+
+        // U64 pointers are immediate, so they are the same in every store.
+        let other = Store::<Fr>::default();
+        let list = store.intern_list_from_iter((0..1000u64).map(|n| other.get_u64(n)));
+        assert_eq!(expected, list);
+
+        let nil = store.nil();
+        assert_eq!(nil, store.intern_list_from_iter(std::iter::empty()));
+    }
+
+    #[test]
+    fn hashed_subtree_is_not_walked_again() {
+        let mut store = Store::<Fr>::default();
+        let shared = store.read("(1 2 3)").unwrap();
+        let a = store.sym("a");
+        let b = store.sym("b");
+        let p1 = store.intern_cons(a, shared);
+        let p2 = store.intern_cons(b, shared);
+        store.hash_expr(&b).unwrap();
+        store.hash_expr(&p1).unwrap();
+
+        // Forget the hashes of the shared list's descendants, keeping its own.
+        let descendants: Vec<_> = {
+            let (_, rest) = store.fetch_cons(&shared).unwrap();
+            let mut rest = *rest;
+            let mut descendants = vec![];
+            while rest.tag() == ExprTag::Cons {
+                let (car, cdr) = *store.fetch_cons(&rest).unwrap();
+                descendants.extend([car, rest]);
+                rest = cdr;
+            }
+            descendants
+        };
+        for ptr in &descendants {
+            store.pointer_scalar_ptr_cache.remove(ptr);
+        }
+        let cached = store.pointer_scalar_ptr_cache.len();
+
+        let expected = store.would_be_cons_hash(&b, &shared).unwrap();
+        assert_eq!(Some(expected), store.hash_expr(&p2));
+        // Only `p2` itself was hashed: the shared list was not walked, so its descendants were not re-cached.
+        assert_eq!(cached + 1, store.pointer_scalar_ptr_cache.len());
+        assert!(descendants
+            .iter()
+            .all(|ptr| !store.pointer_scalar_ptr_cache.contains_key(ptr)));
+    }
+
+    #[test]
+    fn syms_with_prefix() {
+        let mut store = Store::<Fr>::default();
+        let syms = store.intern_syms(&["car", "cdr", "cons", "apple"]);
+        let (car, cdr, cons, apple) = (syms[0], syms[1], syms[2], syms[3]);
+
+        let found = store.syms_with_prefix("C");
+        for ptr in [car, cdr, cons] {
+            assert!(found.contains(&ptr));
+        }
+        assert!(!found.contains(&apple));
+        assert_eq!(store.syms_with_prefix("c"), found);
+        assert_eq!(found, store.syms_with_prefix("lurk.c"));
+
+        let found = store.syms_with_prefix("con");
+        assert!(found.contains(&cons));
+        assert!(!found.contains(&car));
+        assert!(store.syms_with_prefix("zzz").is_empty());
+
+        let nil = store.get_nil();
+        assert_eq!(vec![nil], store.syms_with_prefix("nil"));
+
+        // A top-level symbol matches by its own name.
+        let top = store.read(".cat").unwrap();
+        assert!(store.syms_with_prefix("ca").contains(&top));
+    }
+
+    #[test]
+    fn write_abbreviated_quote() {
+        let mut store = Store::<Fr>::default();
+        let quoted = store.read("(quote foo)").unwrap();
+        let nested = store.read("(a (quote (b . c)) quote (quote))").unwrap();
+        assert_eq!("(QUOTE FOO)", quoted.fmt_to_string(&store));
+
+        store.set_abbreviate_quote(true);
+        assert_eq!("'FOO", quoted.fmt_to_string(&store));
+        // Only a quote form with exactly one argument is abbreviated, and never as a list tail.
+        assert_eq!("(A '(B . C) QUOTE (QUOTE))", nested.fmt_to_string(&store));
+    }
+
+    #[test]
+    fn hash_continuation() {
+        let mut store = Store::<Fr>::default();
+
+        assert_eq!(
+            Some([Fr::from(0u64); 8]),
+            store.get_hash_components_continuation(&Continuation::Outermost)
+        );
+        let outermost = store.intern_cont_outermost();
+        let expected = store.hash_cont(&outermost);
+        assert_eq!(expected, store.hash_continuation(&Continuation::Outermost));
+
+        let saved_env = store.read("((x . 1))").unwrap();
+        let unevaled_args = store.read("(x 2)").unwrap();
+        store.hash_expr(&saved_env).unwrap();
+        store.hash_expr(&unevaled_args).unwrap();
+        let binop = Continuation::Binop {
+            operator: Op2::Product,
+            saved_env,
+            unevaled_args,
+            continuation: outermost,
+        };
+        let binop_count = store.binop_store.len();
+        let hash = store.hash_continuation(&binop).unwrap();
+        assert_eq!(binop_count, store.binop_store.len());
+
+        let ptr = binop.intern_aux(&mut store);
+        assert_eq!(Some(hash), store.hash_cont(&ptr));
+    }
+
+    #[test]
+    fn cons_arena() {
+        let store = &mut Store::<Fr>::with_cons_arena();
+        assert!(!store.dedups_conses());
+        assert!(Store::<Fr>::default().dedups_conses());
+
+        let one = store.intern_num(1);
+        let two = store.intern_num(2);
+        let a = store.intern_cons(one, two);
+        let b = store.intern_cons(one, two);
+
+        // Equal conses are not deduplicated, but both fetch and hash the same.
+        assert_ne!(a, b);
+        assert_eq!(2, store.cons_store.len());
+        assert_eq!(Some(&(one, two)), store.fetch_cons(&a));
+        assert_eq!(Some(&(one, two)), store.fetch_cons(&b));
+        assert_eq!(store.hash_expr(&a), store.hash_expr(&b));
+
+        let list = store.list(&[a, b]);
+        assert_eq!("((1 . 2) (1 . 2))", list.fmt_to_string(store));
+    }
+
+    #[test]
+    fn cons_arena_hydrate() {
+        let store = &mut Store::<Fr>::with_cons_arena();
+        let nil = store.nil();
+        let a = store.read("(lambda (x) (+ x 1))").unwrap();
+        let b = store.read("(lambda (x) (+ x 1))").unwrap();
+        assert_ne!(a, b);
+        // Funs and continuations over distinct copies of a cons are distinct entries with equal hashes too.
+        let fun_a = store.intern_fun(nil, a, nil);
+        let fun_b = store.intern_fun(nil, b, nil);
+        assert_ne!(fun_a, fun_b);
+        let outermost = store.intern_cont_outermost();
+        let cont_a = store
+            .try_intern_cont(Continuation::Tail {
+                saved_env: a,
+                continuation: outermost,
+            })
+            .unwrap();
+        let cont_b = store
+            .try_intern_cont(Continuation::Tail {
+                saved_env: b,
+                continuation: outermost,
+            })
+            .unwrap();
+        assert_ne!(cont_a, cont_b);
+
+        store.hydrate_scalar_cache();
+        let scalar_a = store.hash_expr(&a).unwrap();
+        assert_eq!(Some(scalar_a), store.hash_expr(&b));
+        assert_eq!(store.hash_expr(&fun_a), store.hash_expr(&fun_b));
+        assert_eq!(store.hash_cont(&cont_a), store.hash_cont(&cont_b));
+
+        // The reverse map resolves to one of the copies.
+        let resolved = store.fetch_scalar(&scalar_a).unwrap();
+        assert!(resolved == a || resolved == b);
+        assert!(store.ptr_eq(&a, &b).unwrap());
+    }
+
+    #[test]
+    fn wellknown_digest() {
+        let store = Store::<Fr>::default();
+        let digest = store.wellknown_digest();
+        assert_ne!(Fr::from(0u64), digest);
+
+        // The digest depends only on the well-known symbols and the hashing scheme, not on the rest of the store.
+        let mut other = Store::<Fr>::with_nil_as_symbol(true);
+        other.read("(let ((x 1)) (cons x 'foo))").unwrap();
+        assert_eq!(digest, other.wellknown_digest());
+
+        let arity3 = Store::<Fr>::with_pair_hash(PairHash::Arity3);
+        assert_ne!(digest, arity3.wellknown_digest());
+
+        // Pinned, so that a change to the well-known symbols or to how they are hashed trips this test.
+        #[cfg(not(feature = "sha"))]
+        {
+            assert_eq!(
+                "64e340f7a5e80676043bb984f3b68f31f1d0e99114ff955d2064ae30c4013002",
+                digest.hex_digits()
+            );
+            assert_eq!(
+                "4bd5f1c51ce6ca6e018406a219deb1b43a3695f07055acbea94fb24f59363872",
+                arity3.wellknown_digest().hex_digits()
+            );
+        }
+    }
+
+    #[test]
+    fn intern_str_from_chars() {
+        let mut store = Store::<Fr>::default();
+        let ptr = store.intern_str_from_chars("abc".chars());
+        assert_eq!(store.intern_str("abc"), ptr);
+        assert_eq!(Some("abc"), store.fetch_str(&ptr));
+
+        let rev = store.intern_str_from_chars("abc".chars().rev());
+        assert_eq!(store.intern_str("cba"), rev);
+        assert_eq!(
+            store.intern_str(""),
+            store.intern_str_from_chars(std::iter::empty())
+        );
+    }
+
+    #[test]
+    fn clear_cache_for_tag() {
+        let mut store = Store::<Fr>::default();
+        let num = store.num(123);
+        let sym = store.sym("foo");
+        let num_hash = store.hash_expr(&num).unwrap();
+        let sym_hash = store.hash_expr(&sym).unwrap();
+
+        store.clear_cache_for_tag(ExprTag::Num);
+        assert!(!store.pointer_scalar_ptr_cache.contains_key(&num));
+        assert_eq!(None, store.fetch_scalar(&num_hash));
+        assert!(store.pointer_scalar_ptr_cache.contains_key(&sym));
+        assert_eq!(Some(sym), store.fetch_scalar(&sym_hash));
+
+        // The num is still there, and rehashing it restores its entries.
+        assert!(store.fetch_num(&num).is_some());
+        assert_eq!(Some(num_hash), store.hash_expr(&num));
+        assert!(store.pointer_scalar_ptr_cache.contains_key(&num));
+        assert_eq!(Some(num), store.fetch_scalar(&num_hash));
+    }
+
+    #[test]
+    fn nth_car() {
+        let mut store = Store::<Fr>::default();
+        let list = store.read("(1 2 3)").unwrap();
+        let (one, two, three) = (store.num(1), store.num(2), store.num(3));
+
+        assert_eq!(Some(list), store.nth_car(&list, &[]));
+        assert_eq!(Some(one), store.nth_car(&list, &[CarCdr::Car]));
+        assert_eq!(Some(two), store.cadr(&list));
+        assert_eq!(Some(three), store.caddr(&list));
+        assert_eq!(
+            Some(store.nil()),
+            store.nth_car(&list, &[CarCdr::Cdr, CarCdr::Cdr, CarCdr::Cdr])
+        );
+
+        // Stepping into an atom or past the end of the list fails rather than panicking.
+        assert_eq!(None, store.nth_car(&list, &[CarCdr::Car, CarCdr::Car]));
+        assert_eq!(
+            None,
+            store.nth_car(&list, &[CarCdr::Cdr, CarCdr::Cdr, CarCdr::Cdr, CarCdr::Car])
+        );
+    }
+
+    #[test]
+    fn custom_hasher() {
+        /// Sums its preimage and hashes strings to their length, counting the calls of each.
+        #[derive(Debug, Default)]
+        struct SumHasher(AtomicUsize, AtomicUsize);
+
+        impl SumHasher {
+            fn sum(&self, preimage: &[Fr]) -> Fr {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                preimage.iter().fold(Fr::from(0u64), |acc, x| acc + x)
+            }
+        }
+
+        impl LurkHasher<Fr> for SumHasher {
+            fn hash3(&self, preimage: &[Fr; 3]) -> Fr {
+                self.sum(preimage)
+            }
+            fn hash4(&self, preimage: &[Fr; 4]) -> Fr {
+                self.sum(preimage)
+            }
+            fn hash6(&self, preimage: &[Fr; 6]) -> Fr {
+                self.sum(preimage)
+            }
+            fn hash8(&self, preimage: &[Fr; 8]) -> Fr {
+                self.sum(preimage)
+            }
+            fn hash_string(&self, s: &str) -> Option<Fr> {
+                self.1.fetch_add(1, Ordering::SeqCst);
+                Some(Fr::from(s.len() as u64))
+            }
+        }
+
+        let hasher = Arc::new(SumHasher::default());
+        let mut store = Store::<Fr>::with_hasher(hasher.clone());
+        let one = store.num(1);
+        let two = store.num(2);
+        let cons = store.cons(one, two);
+
+        let calls = hasher.0.load(Ordering::SeqCst);
+        let scalar_ptr = store.hash_expr(&cons).unwrap();
+        assert_eq!(calls + 1, hasher.0.load(Ordering::SeqCst));
+
+        let num_tag = ExprTag::Num.to_field::<Fr>();
+        assert_eq!(
+            num_tag + Fr::from(1u64) + num_tag + Fr::from(2u64),
+            *scalar_ptr.value()
+        );
+
+        let mut default = Store::<Fr>::default();
+        let default_cons = default.read("(1 . 2)").unwrap();
+        assert_ne!(Some(scalar_ptr), default.hash_expr(&default_cons));
+
+        // Strings, and symbols through their path segments, are hashed by `hash_string`.
+        let string_calls = hasher.1.load(Ordering::SeqCst);
+        let str = store.str("hello");
+        assert_eq!(Fr::from(5u64), *store.hash_expr(&str).unwrap().value());
+        assert!(hasher.1.load(Ordering::SeqCst) > string_calls);
+        let sym = store.sym("abc");
+        assert_eq!(
+            ExprTag::Str.to_field::<Fr>() + Fr::from(3u64) + ExprTag::Sym.to_field::<Fr>(),
+            *store.hash_expr(&sym).unwrap().value()
+        );
+
+        // The store rebuilt by gc keeps the hasher.
+        let mut roots = [cons];
+        store.gc(&mut roots);
+        assert_eq!(Some(scalar_ptr), store.hash_expr(&roots[0]));
+    }
+
+    #[test]
+    fn list_kind() {
+        let mut store = Store::<Fr>::default();
+        let nil = store.nil();
+        let list = store.read("(1 2 3)").unwrap();
+        let pair = store.read("(1 . 2)").unwrap();
+        let improper = store.read("(1 2 . 3)").unwrap();
+        let num = store.num(1);
+        let str = store.str("abc");
+
+        assert_eq!(ListKind::Proper(0), store.list_kind(&nil));
+        assert_eq!(ListKind::Proper(3), store.list_kind(&list));
+        assert_eq!(ListKind::Improper, store.list_kind(&pair));
+        assert_eq!(ListKind::Improper, store.list_kind(&improper));
+        assert_eq!(ListKind::Atom, store.list_kind(&num));
+        assert_eq!(ListKind::Atom, store.list_kind(&str));
+    }
+
+    #[test]
+    fn hydrate_and_hash_roots() {
+        let sources = ["(1 2 3)", "\"abc\"", "(lambda (x) (+ x 1))", "foo", "123"];
+
+        let mut store = Store::<Fr>::default();
+        let mut roots: Vec<_> = sources.iter().map(|s| store.read(s).unwrap()).collect();
+        roots.push(store.get_nil());
+        let hashes = store.hydrate_and_hash_roots(&roots);
+        assert!(store.dehydrated.is_empty());
+
+        let mut other = Store::<Fr>::default();
+        let mut other_roots: Vec<_> = sources.iter().map(|s| other.read(s).unwrap()).collect();
+        other_roots.push(other.get_nil());
+        other.hydrate_scalar_cache();
+        let expected: Vec<_> = other_roots
+            .iter()
+            .map(|root| other.hash_expr(root))
+            .collect();
+
+        assert_eq!(roots.len(), hashes.len());
+        assert!(hashes.iter().all(Option::is_some));
+        assert_eq!(expected, hashes);
+    }
+
+    #[test]
+    fn expression_accessors() {
+        let mut store = Store::<Fr>::default();
+        let num = store.num(123);
+        let cons = store.read("(1 . 2)").unwrap();
+        let x = store.sym("x");
+        let env = store.nil();
+        let fun = store.intern_fun(x, x, env);
+        let (one, two) = (store.num(1), store.num(2));
+
+        let num_expr = store.fetch(&num).unwrap();
+        let cons_expr = store.fetch(&cons).unwrap();
+        let fun_expr = store.fetch(&fun).unwrap();
+
+        assert_eq!(Some(Num::U64(123)), num_expr.as_num());
+        assert_eq!(None, cons_expr.as_num());
+
+        assert_eq!(Some((one, two)), cons_expr.as_cons());
+        assert_eq!(None, num_expr.as_cons());
+        assert_eq!(None, Expression::<Fr>::Nil.as_cons());
+
+        assert_eq!(Some((x, x, env)), fun_expr.as_fun());
+        assert_eq!(None, cons_expr.as_fun());
+    }
+
+    #[test]
+    fn is_valid_env() {
+        let mut store = Store::<Fr>::default();
+        let valid = |store: &mut Store<Fr>, src: &str| {
+            let env = store.read(src).unwrap();
+            store.is_valid_env(&env)
+        };
+
+        assert!(valid(&mut store, "nil"));
+        assert!(valid(&mut store, "((x . 1) (y . \"abc\"))"));
+        assert!(valid(&mut store, "(((f . 1) (g . 2)) (x . 1))"));
+
+        // A binding whose variable is not a symbol.
+        assert!(!valid(&mut store, "((x . 1) (2 . 3))"));
+        assert!(!valid(&mut store, "((\"x\" . 1))"));
+        // An element that is neither a binding nor a recursive frame.
+        assert!(!valid(&mut store, "((x . 1) 2)"));
+        assert!(!valid(&mut store, "(nil)"));
+        // An improper list of bindings.
+        assert!(!valid(&mut store, "((x . 1) . 2)"));
+        assert!(!valid(&mut store, "(((f . 1) . 2))"));
+        // Not a list at all.
+        assert!(!valid(&mut store, "123"));
+    }
+
+    #[test]
+    fn op_hash_components() {
+        let mut store = Store::<Fr>::default();
+        let zero = Fr::from(0u64);
+        assert_eq!(
+            [Op1::Car.to_field(), zero],
+            store.op1_hash_components(&Op1::Car)
+        );
+        assert_eq!(
+            [Op2::Sum.to_field(), zero],
+            store.op2_hash_components(&Op2::Sum)
+        );
+
+        // They match the components the store hashes continuations with.
+        let outermost = store.intern_cont_outermost();
+        let unop = Continuation::Unop {
+            operator: Op1::Car,
+            continuation: outermost,
+        };
+        let components = store.get_hash_components_continuation(&unop).unwrap();
+        assert_eq!(store.op1_hash_components(&Op1::Car), components[..2]);
+    }
+
+    #[test]
+    fn from_reader() {
+        use crate::light_data::LightExpr;
+
+        let mut store = Store::<Fr>::default();
+        let cons = store.read("(1 . 2)").unwrap();
+        let (one, two) = (store.num(1), store.num(2));
+        let cons_ptr = store.hash_expr(&cons).unwrap();
+        let light_store = LightStore {
+            scalar_map: [(
+                cons_ptr,
+                Some(LightExpr::Cons(
+                    store.hash_expr(&one).unwrap(),
+                    store.hash_expr(&two).unwrap(),
+                )),
+            )]
+            .into_iter()
+            .collect(),
+        };
+        let bytes = light_store.ser().ser();
+
+        // The store decoded in full, one step at a time.
+        let light_data = LightData::de(&bytes).unwrap();
+        let mut scalar_store =
+            ScalarStore::try_from(LightStore::<Fr>::de(&light_data).unwrap()).unwrap();
+        let expected = scalar_store.to_store().unwrap();
+        let expected_ptr = expected.fetch_scalar(&cons_ptr).unwrap();
+
+        let from_reader = Store::<Fr>::from_reader(std::io::Cursor::new(&bytes)).unwrap();
+        let ptr = from_reader.fetch_scalar(&cons_ptr).unwrap();
+        assert_eq!(expected_ptr, ptr);
+        assert_eq!(expected.entry_count(), from_reader.entry_count());
+        assert_eq!("(1 . 2)", ptr.fmt_to_string(&from_reader));
+        assert_eq!(
+            ScalarStore::new_with_expr(&expected, &expected_ptr),
+            ScalarStore::new_with_expr(&from_reader, &ptr)
+        );
+        assert_eq!(
+            ScalarStore::new_with_expr(&store, &cons),
+            ScalarStore::new_with_expr(&from_reader, &ptr)
+        );
+
+        assert!(matches!(
+            Store::<Fr>::from_reader(&bytes[..bytes.len() - 1]),
+            Err(StoreError::InvalidLightStore(_))
+        ));
+    }
+
+    #[test]
+    fn from_reader_stops_at_first_bad_entry() {
+        use crate::light_data::LightExpr;
+
+        let mut store = Store::<Fr>::default();
+        let mut list = store.num(100);
+        for n in (0..100).rev() {
+            let num = store.num(n);
+            list = store.cons(num, list);
+        }
+        let mut scalar_map = BTreeMap::new();
+        let mut rest = list;
+        while let Some((car, cdr)) = store.fetch_cons(&rest).copied() {
+            let expr = LightExpr::Cons(
+                store.hash_expr(&car).unwrap(),
+                store.hash_expr(&cdr).unwrap(),
+            );
+            scalar_map.insert(store.hash_expr(&rest).unwrap(), Some(expr));
+            rest = cdr;
+        }
+        let list_ptr = store.hash_expr(&list).unwrap();
+
+        let bytes = LightStore {
+            scalar_map: scalar_map.clone(),
+        }
+        .ser()
+        .ser();
+        let loaded = Store::<Fr>::from_bytes(&bytes).unwrap();
+        let loaded_list = loaded.fetch_scalar(&list_ptr).unwrap();
+        assert_eq!(
+            list.fmt_to_string(&store),
+            loaded_list.fmt_to_string(&loaded)
+        );
+
+        // Give the first entry a cons pointer but a nil expression.
+        let first = *scalar_map.keys().next().unwrap();
+        scalar_map.insert(first, Some(LightExpr::Nil));
+        let entries = scalar_map.into_iter().collect::<Vec<_>>();
+        let bytes = entries.ser().ser();
+        let mut reader = std::io::Cursor::new(&bytes);
+        assert!(matches!(
+            Store::<Fr>::from_reader(&mut reader),
+            Err(StoreError::InvalidLightStore(e)) if e.contains("Unsupported pair")
+        ));
+
+        // Nothing past the first entry was read.
+        let entry_lens = entries
+            .iter()
+            .map(|entry| entry.ser().ser().len())
+            .collect::<Vec<_>>();
+        let header_len = bytes.len() - entry_lens.iter().sum::<usize>();
+        assert_eq!((header_len + entry_lens[0]) as u64, reader.position());
+    }
+
+    #[test]
+    fn extract_reachable_multi() {
+        let mut store = Store::<Fr>::default();
+        let expr = store.read("(+ 1 2)").unwrap();
+        let env = store.read("((x . 1))").unwrap();
+        let args = store.read("(3 4)").unwrap();
+        store.read("(unrelated list)").unwrap();
+        let outermost = store.intern_cont_outermost();
+        let binop = store
+            .try_intern_cont_binop(Op2::Sum, env, args, outermost)
+            .unwrap();
+
+        let (extract, exprs, conts) = store.extract_reachable_multi(&[expr, env], &[binop]);
+        assert_eq!(store.hash_expr(&expr), extract.hash_expr(&exprs[&expr]));
+        assert_eq!(store.hash_expr(&env), extract.hash_expr(&exprs[&env]));
+        assert_eq!(store.hash_cont(&binop), extract.hash_cont(&conts[&binop]));
+        assert_eq!(
+            store.hash_cont(&outermost),
+            extract.hash_cont(&conts[&outermost])
+        );
+
+        // `env` is reachable from both roots but copied once; the unrelated list is not copied.
+        assert_eq!(3 + 2 + 2, extract.cons_store.len());
+        assert_eq!(1, extract.binop_store.len());
+    }
+
+    #[test]
+    fn intern_cons_with_index() {
+        let mut a = Store::<Fr>::default();
+        let mut b = Store::<Fr>::default();
+        a.read("(1 2 3)").unwrap();
+
+        // Both stores put the same cons at index 5, although they held different numbers of conses.
+        let (one_a, two_a) = (a.num(1), a.num(2));
+        let (one_b, two_b) = (b.num(1), b.num(2));
+        let ptr_a = a.intern_cons_with_index(5, one_a, two_a).unwrap();
+        let ptr_b = b.intern_cons_with_index(5, one_b, two_b).unwrap();
+        assert_eq!(5, ptr_a.1.idx());
+        assert_eq!(ptr_a, ptr_b);
+        assert_eq!(a.hash_expr(&ptr_a), b.hash_expr(&ptr_b));
+        assert_eq!(6, b.cons_store.len());
+        assert!(b.validate().is_ok());
+
+        // Re-interning the same cons at its slot succeeds; anything else conflicts.
+        assert_eq!(Ok(ptr_a), a.intern_cons_with_index(5, one_a, two_a));
+        assert_eq!(
+            Err(StoreError::SlotOccupied {
+                store: "cons_store",
+                index: 5
+            }),
+            a.intern_cons_with_index(5, two_a, one_a)
+        );
+        assert_eq!(
+            Err(StoreError::InternedElsewhere {
+                store: "cons_store",
+                index: 7,
+                existing: 5
+            }),
+            a.intern_cons_with_index(7, one_a, two_a)
+        );
+
+        // A cons which has the shape of a placeholder list of nils still lands in its slot.
+        let mut store = Store::<Fr>::default();
+        let nil = store.nil();
+        let ptr = store.intern_cons_with_index(3, nil, nil).unwrap();
+        assert_eq!(3, ptr.1.idx());
+        assert_eq!(Some(&(nil, nil)), store.fetch_cons(&ptr));
+        let pair = store.intern_cons_with_index(5, nil, ptr).unwrap();
+        assert_eq!(5, pair.1.idx());
+        assert_eq!(Some(&(nil, ptr)), store.fetch_cons(&pair));
+        assert_eq!(6, store.cons_store.len());
+    }
+
+    #[test]
+    fn ptr_has_hash() {
+        let mut store = Store::<Fr>::default();
+        let cons = store.read("(1 . 2)").unwrap();
+        let other = store.read("(2 . 1)").unwrap();
+        let expected = store.hash_expr(&cons).unwrap();
+
+        assert!(store.ptr_has_hash(&cons, &expected));
+        assert!(!store.ptr_has_hash(&other, &expected));
+
+        // Same value, different tag: rejected before hashing `cons`.
+        let mistagged = ScalarPtr::from_parts(ExprTag::Comm, *expected.value());
+        store.pointer_scalar_ptr_cache.clear();
+        assert!(!store.ptr_has_hash(&cons, &mistagged));
+        assert!(!store.pointer_scalar_ptr_cache.contains_key(&cons));
+    }
+
+    #[test]
+    fn cont_components_iter() {
+        let mut store = Store::<Fr>::default();
+        let env = store.read("((x . 1))").unwrap();
+        let args = store.read("(3 4)").unwrap();
+        let outermost = store.intern_cont_outermost();
+        let binop = store
+            .try_intern_cont_binop(Op2::Sum, env, args, outermost)
+            .unwrap();
+        let binop2 = store
+            .try_intern_cont(Continuation::Binop2 {
+                operator: Op2::Product,
+                evaled_arg: args,
+                continuation: binop,
+            })
+            .unwrap();
+        store
+            .try_intern_cont(Continuation::Lookup {
+                saved_env: env,
+                continuation: binop2,
+            })
+            .unwrap();
+
+        let components: Vec<[Fr; 8]> = store.cont_components_iter().collect();
+        let entries = store.binop_store.len() + store.binop2_store.len() + store.lookup_store.len();
+        assert_eq!(3, entries);
+        assert_eq!(entries, components.len());
+        assert!(components.contains(&store.get_hash_components_cont(&binop2).unwrap()));
+    }
+
+    #[test]
+    fn hash_expr_partial() {
+        let mut store = Store::<Fr>::default();
+        let complete = store.read("(1 (2 3))").unwrap();
+        assert_eq!(
+            PartialHash::Full(store.hash_expr(&complete).unwrap()),
+            store.hash_expr_partial(&complete)
+        );
+
+        let one = store.num(1);
+        let dangling_cons = Ptr(ExprTag::Cons, RawPtr::new(store.cons_store.len() + 100));
+        let dangling_num = Ptr(ExprTag::Num, RawPtr::new(store.num_store.len() + 100));
+        let inner = store.intern_cons(dangling_num, one);
+        let inner = store.intern_cons(one, inner);
+        let outer = store.intern_cons(inner, dangling_cons);
+        let outer = store.intern_cons(complete, outer);
+
+        assert_eq!(
+            PartialHash::Missing(vec![dangling_num, dangling_cons]),
+            store.hash_expr_partial(&outer)
+        );
+        assert_eq!(
+            PartialHash::Missing(vec![dangling_cons]),
+            store.hash_expr_partial(&dangling_cons)
+        );
+        // The complete parts are still hashed.
+        assert!(store.hash_expr(&one).is_some());
+        assert!(store.hash_expr(&complete).is_some());
+    }
+
+    #[test]
+    fn clone_without_caches() {
+        let mut store = Store::<Fr>::default();
+        let expr = store
+            .read("(let ((f (lambda (x) (+ x 1)))) (cons \"str\" (f 2)))")
+            .unwrap();
+        let comm = store.hide(Fr::from(42u64), expr);
+        let opaque = store.intern_opaque_cons(Fr::from(7u64));
+        let outermost = store.intern_cont_outermost();
+        let cont = store
+            .try_intern_cont(Continuation::Lookup {
+                saved_env: expr,
+                continuation: outermost,
+            })
+            .unwrap();
+        store.hydrate_scalar_cache();
+
+        let mut clone = store.clone_without_caches();
+        assert_eq!((0, 0), clone.reverse_map_sizes());
+        assert_eq!((0, 0, 0), clone.cache_sizes());
+        assert!(clone.pointer_scalar_ptr_cache.is_empty());
+        assert_eq!(store.substore_sizes(), clone.substore_sizes());
+
+        assert_eq!(store.fetch(&expr), clone.fetch(&expr));
+        assert_eq!(store.fetch(&comm), clone.fetch(&comm));
+        assert_eq!(store.fetch_cont(&cont), clone.fetch_cont(&cont));
+        assert_eq!(expr.fmt_to_string(&store), expr.fmt_to_string(&clone));
+
+        clone.hydrate_scalar_cache();
+        for ptr in [expr, comm, opaque] {
+            let scalar_ptr = store.hash_expr(&ptr).unwrap();
+            assert_eq!(Some(scalar_ptr), clone.hash_expr(&ptr));
+            assert_eq!(Some(ptr), clone.fetch_scalar(&scalar_ptr));
+        }
+        assert_eq!(store.hash_cont(&cont), clone.hash_cont(&cont));
+        assert_eq!(store.sym("lambda"), clone.sym("lambda"));
+        assert_eq!(store.intern_str("str"), clone.intern_str("str"));
+    }
+
+    #[test]
+    fn cache_warmth() {
+        let mut store = Store::<Fr>::default();
+        let expr = store
+            .read("(letrec ((f (lambda (x) (if x (f (cdr x)) nil)))) (f '(1 2 3)))")
+            .unwrap();
+        let outermost = store.intern_cont_outermost();
+        store
+            .try_intern_cont(Continuation::Lookup {
+                saved_env: expr,
+                continuation: outermost,
+            })
+            .unwrap();
+        assert!(store.cache_warmth() < 1e-9);
+
+        store.hydrate_scalar_cache();
+        assert!(store.cache_warmth() > 1.0 - 1e-9);
+
+        store.intern_num(7);
+        store.intern_str("not hashable by cache_warmth");
+        assert!(store.cache_warmth() > 1.0 - 1e-9);
+
+        let car = store.num(7);
+        store.cons(car, expr);
+        let warmth = store.cache_warmth();
+        assert!(0.0 < warmth && warmth < 1.0);
+    }
+
+    #[test]
+    fn value_as_u64() {
+        let mut store = Store::<Fr>::default();
+        let num = store.num(65);
+        let scalar_ptr = store.hash_expr(&num).unwrap();
+        assert_eq!(Some(65), scalar_ptr.value_as_u64());
+
+        let max = ScalarPtr::from_parts(ExprTag::Num, Fr::from(u64::MAX));
+        assert_eq!(Some(u64::MAX), max.value_as_u64());
+        let large = ScalarPtr::from_parts(ExprTag::Num, Fr::from(u64::MAX) + Fr::from(1u64));
+        assert_eq!(None, large.value_as_u64());
+    }
+
+    #[test]
+    fn shares_subtree() {
+        let mut store = Store::<Fr>::with_cons_arena();
+        let shared = store.read("(1 (2 . \"two\") 3)").unwrap();
+        let copy = store.read("(1 (2 . \"two\") 3)").unwrap();
+        assert!(store.ptr_eq(&shared, &copy).unwrap());
+        assert!(!store.shares_subtree(&shared, &copy));
+        assert!(store.shares_subtree(&shared, &shared));
+
+        let expr = store.cons(shared, shared);
+        let expr = store.cons(expr, copy);
+        store.hydrate_scalar_cache();
+
+        let (mut scalar_store, scalar_ptr) = ScalarStore::new_with_expr(&store, &expr);
+        let (store2, expr2) = scalar_store
+            .to_store_with_expr(&scalar_ptr.unwrap())
+            .unwrap();
+        let (inner, copy2) = store2.car_cdr(&expr2).unwrap();
+        let (car2, cdr2) = store2.car_cdr(&inner).unwrap();
+        assert!(store2.shares_subtree(&car2, &cdr2));
+        // Equal subtrees have the same `ScalarPtr`, so the round-trip merges the two copies.
+        assert!(store2.shares_subtree(&car2, &copy2));
+        assert_eq!(shared.fmt_to_string(&store), copy2.fmt_to_string(&store2));
+    }
+
+    #[test]
+    fn expr_children() {
+        let mut store = Store::<Fr>::default();
+        let num = store.num(123);
+        assert_eq!(
+            Some((ExprTag::Num.to_field::<Fr>(), Vec::new())),
+            store.expr_children(&num)
+        );
+
+        let nil = store.nil();
+        let cons = store.cons(num, nil);
+        assert_eq!(
+            Some((ExprTag::Cons.to_field::<Fr>(), vec![num, nil])),
+            store.expr_children(&cons)
+        );
+
+        let arg = store.sym("x");
+        let body = store.list(&[arg]);
+        let env = store.nil();
+        let fun = store.intern_fun(arg, body, env);
+        assert_eq!(
+            Some((ExprTag::Fun.to_field::<Fr>(), vec![arg, body, env])),
+            store.expr_children(&fun)
+        );
+
+        let comm = store.hide(Fr::from(1u64), cons);
+        assert_eq!(
+            Some((ExprTag::Comm.to_field::<Fr>(), vec![cons])),
+            store.expr_children(&comm)
+        );
+
+        let opaque = store.intern_opaque_cons(Fr::from(7u64));
+        assert_eq!(
+            Some((ExprTag::Cons.to_field::<Fr>(), Vec::new())),
+            store.expr_children(&opaque)
+        );
+        let missing = Ptr(ExprTag::Cons, RawPtr::new(1000));
+        assert_eq!(None, store.expr_children(&missing));
+    }
+
+    #[test]
+    fn reachable_conts() {
+        let mut store = Store::<Fr>::default();
+        let env = store.read("((x . 1))").unwrap();
+        let outermost = store.intern_cont_outermost();
+        let tail = store
+            .try_intern_cont(Continuation::Tail {
+                saved_env: env,
+                continuation: outermost,
+            })
+            .unwrap();
+        let value = store.num(2);
+        let thunk = store.intern_thunk(Thunk {
+            value,
+            continuation: tail,
+        });
+        let expr = store.list(&[value, thunk, thunk]);
+        assert_eq!(vec![tail, outermost], store.reachable_conts(&expr));
+
+        // Continuations are followed into the expressions they reference, and from there into further thunks.
+        let terminal = store.intern_cont_terminal();
+        let inner = store.intern_thunk(Thunk {
+            value,
+            continuation: terminal,
+        });
+        let binop2 = store
+            .try_intern_cont(Continuation::Binop2 {
+                operator: Op2::Sum,
+                evaled_arg: inner,
+                continuation: outermost,
+            })
+            .unwrap();
+        let outer = store.intern_thunk(Thunk {
+            value,
+            continuation: binop2,
+        });
+        assert_eq!(
+            vec![binop2, terminal, outermost],
+            store.reachable_conts(&outer)
+        );
+
+        assert!(store.reachable_conts(&env).is_empty());
+    }
+
+    #[test]
+    fn scalar_ptr_debug() {
+        let mut store = Store::<Fr>::default();
+        let cons = store.read("(1 . 2)").unwrap();
+        let scalar_ptr = store.hash_expr(&cons).unwrap();
+        let debug = format!("{scalar_ptr:?}");
+        assert!(debug.starts_with("ScalarPtr { tag: Cons, value: 0x"));
+        assert!(debug.contains(&scalar_ptr.value().hex_digits()));
+
+        let outermost = store.intern_cont_outermost();
+        let scalar_cont_ptr = store.hash_cont(&outermost).unwrap();
+        assert!(format!("{scalar_cont_ptr:?}").starts_with("ScalarContPtr { tag: Outermost, "));
+    }
+
+    #[test]
+    fn intern_conses() {
+        let mut store = Store::<Fr>::default();
+        let (one, two, nil) = (store.num(1), store.num(2), store.nil());
+        let existing = store.intern_cons(one, nil);
+
+        let pairs = [(one, two), (two, one), (one, two), (one, nil), (two, one)];
+        let (ptrs, new) = store.intern_conses(&pairs);
+        assert_eq!(2, new);
+        assert_eq!(pairs.len(), ptrs.len());
+        assert_eq!(ptrs[0], ptrs[2]);
+        assert_eq!(ptrs[1], ptrs[4]);
+        assert_eq!(existing, ptrs[3]);
+        for (ptr, (car, cdr)) in ptrs.iter().zip(pairs) {
+            assert_eq!(Some(&(car, cdr)), store.fetch_cons(ptr));
+        }
+
+        assert_eq!((ptrs.clone(), 0), store.intern_conses(&pairs));
+
+        let mut arena = Store::<Fr>::with_cons_arena();
+        let (one, two) = (arena.num(1), arena.num(2));
+        let (_, new) = arena.intern_conses(&[(one, two), (one, two)]);
+        assert_eq!(2, new);
+    }
+
+    #[test]
+    fn max_value_bits() {
+        let mut store = Store::<Fr>::default();
+        assert_eq!(0, store.max_value_bits());
+
+        for n in [0u64, 1, 255, 1 << 19, 1 << 20] {
+            store.intern_num(n);
+        }
+        assert_eq!(21, store.max_value_bits());
+
+        // Representations don't matter.
+        store.intern_num(Num::Scalar(Fr::from(1u64 << 40)));
+        assert_eq!(41, store.max_value_bits());
+
+        store.intern_num(Num::Scalar(Fr::from(0u64) - Fr::from(1u64)));
+        // The BLS12-381 scalar field modulus, and so p - 1, is 255 bits long.
+        assert_eq!(255, store.max_value_bits());
+    }
+
+    #[test]
+    fn opaque_hash() {
+        let mut store = Store::<Fr>::default();
+        let hash = Fr::from(123u64);
+        let opaque_cons = store.intern_opaque_cons(hash);
+        assert_eq!(Some(hash), store.opaque_hash(&opaque_cons));
+        let opaque_fun = store.intern_opaque_fun(Fr::from(456u64));
+        assert_eq!(Some(Fr::from(456u64)), store.opaque_hash(&opaque_fun));
+
+        let cons = store.read("(1 . 2)").unwrap();
+        assert_eq!(None, store.opaque_hash(&cons));
+        // Interning the hash of a known cons as maybe-opaque yields the concrete cons.
+        let cons_hash = *store.hash_expr(&cons).unwrap().value();
+        let maybe_opaque = store.intern_maybe_opaque_cons(cons_hash);
+        assert_eq!(None, store.opaque_hash(&maybe_opaque));
+        let opaque = store.intern_opaque_cons(cons_hash);
+        assert_eq!(Some(cons_hash), store.opaque_hash(&opaque));
+    }
+
+    #[test]
+    fn hash_expr_traced() {
+        let mut store = Store::<Fr>::default();
+        let cons = store.read("(1 . 2)").unwrap();
+        store.hydrate_scalar_cache();
+        let cache_sizes = store.cache_sizes();
+
+        let (scalar_ptr, steps) = store.hash_expr_traced(&cons);
+        assert_eq!(cache_sizes, store.cache_sizes());
+        assert_eq!(store.hash_expr(&cons), scalar_ptr);
+        let num = ExprTag::Num.to_field::<Fr>();
+        assert_eq!(
+            vec![HashStep {
+                arity: 4,
+                preimage: vec![num, Fr::from(1u64), num, Fr::from(2u64)],
+            }],
+            steps
+        );
+        assert_eq!(
+            *scalar_ptr.unwrap().value(),
+            PoseidonHasher::default().hash4(&[num, Fr::from(1u64), num, Fr::from(2u64)])
+        );
+
+        // Shared subexpressions are hashed once.
+        let pair = store.read("((1 . 2) . (1 . 2))").unwrap();
+        let (scalar_ptr, steps) = store.hash_expr_traced(&pair);
+        assert_eq!(store.hash_expr(&pair), scalar_ptr);
+        assert_eq!(2, steps.len());
+        assert!(steps.iter().all(|step| step.arity == 4));
+
+        // Neither the store's caches nor its reverse maps are touched.
+        let expr = store.read("(lambda (x) (cons :key \"str\"))").unwrap();
+        let x = store.sym("x");
+        let fun = store.intern_fun(x, expr, store.get_nil());
+        let outermost = store.intern_cont_outermost();
+        let lookup = store
+            .try_intern_cont(Continuation::Lookup {
+                saved_env: expr,
+                continuation: outermost,
+            })
+            .unwrap();
+        let thunk = store.intern_thunk(Thunk {
+            value: fun,
+            continuation: lookup,
+        });
+        let (cache_sizes, reverse_map_sizes) = (store.cache_sizes(), store.reverse_map_sizes());
+        let (scalar_ptr, _) = store.hash_expr_traced(&thunk);
+        assert_eq!(cache_sizes, store.cache_sizes());
+        assert_eq!(reverse_map_sizes, store.reverse_map_sizes());
+        assert!(!store.pointer_scalar_ptr_cache.contains_key(&thunk));
+        assert_eq!(store.hash_expr(&thunk), scalar_ptr);
+    }
+
+    #[test]
+    fn reset() {
+        let names = ["defmacro", "unquote"];
+        let mut store = Store::<Fr>::with_wellknown_syms(&names);
+        let fresh = Store::<Fr>::with_wellknown_syms(&names);
+        let vocabulary: Vec<_> = names.iter().map(|name| store.sym(name)).collect();
+        let (nil, t, lambda) = (store.get_nil(), store.get_t(), store.sym("lambda"));
+
+        let expr = store.read("(defmacro f (x) \"user data\" 123)").unwrap();
+        let user_sym = store.sym("my-symbol");
+        store.hydrate_scalar_cache();
+        assert!(store.fetch(&expr).is_some());
+        // A renamed vocabulary symbol comes back under its original name.
+        let old_name = store.sym_name_cow(&vocabulary[1]).unwrap().into_owned();
+        let new_name = old_name.replace("UNQUOTE", "RENAMED");
+        store.rename_sym(&old_name, &new_name).unwrap();
+
+        store.reset();
+        assert_eq!(fresh.substore_sizes(), store.substore_sizes());
+        assert!(store.fetch_cons(&expr).is_none());
+        assert!(store.fetch_sym(&user_sym).is_none());
+        assert_eq!((0, 0), store.reverse_map_sizes());
+
+        assert_eq!(nil, store.get_nil());
+        assert_eq!(t, store.get_t());
+        assert_eq!(lambda, store.sym("lambda"));
+        for (name, ptr) in names.iter().zip(vocabulary) {
+            assert_eq!(ptr, store.sym(name));
+        }
+        assert!(!store.contains_sym(&new_name));
+        let outermost = store.get_cont_outermost();
+        assert!(store.fetch_cont(&outermost).is_some());
+        assert_eq!(fresh.hash_expr(&nil), store.hash_expr(&nil));
+    }
+
+    #[test]
+    fn from_bytes_rejects_corrupted_bytes() {
+        use crate::light_data::LightExpr;
+
+        let mut store = Store::<Fr>::default();
+        let outer = store.read("((1 . 2) . 3)").unwrap();
+        let inner = store.car(&outer).unwrap();
+        let ptrs = [outer, inner, store.num(1), store.num(2), store.num(3)];
+        let [outer_ptr, inner_ptr, one, two, three] =
+            ptrs.map(|ptr| store.hash_expr(&ptr).unwrap());
+        let light_store = LightStore {
+            scalar_map: [
+                (outer_ptr, Some(LightExpr::Cons(inner_ptr, three))),
+                (inner_ptr, Some(LightExpr::Cons(one, two))),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let bytes = light_store.ser().ser();
+        assert!(Store::<Fr>::from_bytes(&bytes).is_ok());
+
+        // The inner cons's pointer is serialized twice: as its own key and as the car of the outer cons. Changing one
+        // of them leaves the outer cons pointing at an entry the bytes don't contain.
+        let needle = inner_ptr.ser().ser();
+        let positions: Vec<usize> = bytes
+            .windows(needle.len())
+            .enumerate()
+            .filter(|(_, window)| *window == needle.as_slice())
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(2, positions.len());
+        let mut corrupted = bytes.clone();
+        corrupted[positions[0] + needle.len() - 1] ^= 1;
+        assert!(matches!(
+            Store::<Fr>::from_bytes(&corrupted),
+            Err(StoreError::InvalidLightStore(_))
+        ));
+
+        // Bytes that don't decode as `LightData` at all.
+        let mut corrupted = bytes;
+        corrupted[0] ^= 0xff;
+        assert!(matches!(
+            Store::<Fr>::from_bytes(&corrupted),
+            Err(StoreError::InvalidLightStore(_))
+        ));
+    }
+
+    #[test]
+    fn expr_depth_and_node_count() {
+        let mut store = Store::<Fr>::default();
+        let nil = store.nil();
+        let one = store.num(1);
+        assert_eq!(0, store.expr_depth(&nil));
+        assert_eq!(1, store.node_count(&nil));
+        assert_eq!(0, store.expr_depth(&one));
+
+        // Three conses, three nums and nil.
+        let flat = store.read("(1 2 3)").unwrap();
+        assert_eq!(3, store.expr_depth(&flat));
+        assert_eq!(7, store.node_count(&flat));
+
+        let balanced = store.read("((1 . 2) . (3 . 4))").unwrap();
+        assert_eq!(2, store.expr_depth(&balanced));
+        assert_eq!(7, store.node_count(&balanced));
+
+        // The shared pair is only counted once.
+        let pair = store.read("(1 . 2)").unwrap();
+        let dag = store.cons(pair, pair);
+        assert_eq!(2, store.expr_depth(&dag));
+        assert_eq!(4, store.node_count(&dag));
+
+        let mut deep = nil;
+        for _ in 0..100_000 {
+            deep = store.cons(deep, nil);
+        }
+        assert_eq!(100_000, store.expr_depth(&deep));
+        assert_eq!(100_001, store.node_count(&deep));
+    }
+
+    #[test]
+    fn assert_same_hash() {
+        let src = "(lambda (x) (cons x \"y\"))";
+        let mut store1 = Store::<Fr>::default();
+        let mut store2 = Store::<Fr>::default();
+        // Intern something else first, so the two expressions have different `Ptr`s.
+        store2.read("(1 2 3)").unwrap();
+        let a = store1.read(src).unwrap();
+        let b = store2.read(src).unwrap();
+
+        store1.assert_same_hash(&store2, &a, &b);
+    }
+
+    #[test]
+    #[should_panic(expected = "hashes differ: left (ptr->num#, 1) != right (ptr->num#, 2)")]
+    fn assert_same_hash_mismatch() {
+        let mut store1 = Store::<Fr>::default();
+        let mut store2 = Store::<Fr>::default();
+        let a = store1.num(1);
+        let b = store2.num(2);
+
+        store1.assert_same_hash(&store2, &a, &b);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn hydration_tracing_span() {
+        use std::sync::Mutex;
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+        use tracing_subscriber::Layer;
+
+        struct FieldRecorder<'a>(&'a mut HashMap<String, u64>);
+
+        impl Visit for FieldRecorder<'_> {
+            fn record_u64(&mut self, field: &Field, value: u64) {
+                self.0.insert(field.name().to_string(), value);
+            }
+
+            fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+        }
+
+        #[derive(Clone, Default)]
+        struct SpanFields(Arc<Mutex<HashMap<String, u64>>>);
+
+        impl<S: tracing::Subscriber> Layer<S> for SpanFields {
+            fn on_record(
+                &self,
+                _id: &tracing::span::Id,
+                values: &tracing::span::Record<'_>,
+                _ctx: Context<'_, S>,
+            ) {
+                values.record(&mut FieldRecorder(&mut self.0.lock().unwrap()));
+            }
+        }
+
+        let mut store = Store::<Fr>::default();
+        store.read("(1 2 3)").unwrap();
+        store.intern_cont_outermost();
+
+        let fields = SpanFields::default();
+        let subscriber = tracing_subscriber::registry().with(fields.clone());
+        tracing::subscriber::with_default(subscriber, || store.hydrate_scalar_cache());
+
+        let fields = fields.0.lock().unwrap();
+        assert_eq!(Some(&3), fields.get("cons"));
+        assert_eq!(Some(&0), fields.get("fun"));
+        assert_eq!(Some(&1), fields.get("cont"));
+        assert!(fields.contains_key("duration_us"));
+    }
+
+    #[test]
+    fn intern_num_dedups_equal_values() {
+        let mut store = Store::<Fr>::default();
+
+        let five = store.intern_num(Num::<Fr>::U64(5));
+        assert_eq!(five, store.intern_num(Num::Scalar(Fr::from(5u64))));
+        assert_eq!(
+            five,
+            store.intern_num(Num::from_scalar(Fr::from(2u64) + Fr::from(3u64)))
+        );
+
+        // Overflowing u64 arithmetic yields a `Scalar`, which is normalized back when it fits again.
+        let mut n = Num::<Fr>::U64(u64::MAX);
+        n += Num::U64(1);
+        n -= Num::U64(1);
+        assert!(matches!(n, Num::Scalar(_)));
+        assert_eq!(store.intern_num(u64::MAX), store.intern_num(n));
+
+        let mut minus_one = Num::<Fr>::U64(0);
+        minus_one -= Num::U64(1);
+        assert_eq!(
+            store.intern_num(minus_one),
+            store.intern_num(Num::Scalar(Fr::from(0u64) - Fr::from(1u64)))
+        );
+        assert_eq!(Some(five), store.get_num(Num::Scalar(Fr::from(5u64))));
+    }
+
+    #[test]
+    fn intern_syms() {
+        let mut store = Store::<Fr>::default();
+        let syms = store.intern_syms(&["apple", "banana", "APPLE", "lambda"]);
+
+        assert_eq!(
+            vec![
+                store.sym("apple"),
+                store.sym("banana"),
+                store.sym("apple"),
+                store.sym("lambda")
+            ],
+            syms
+        );
+        assert_eq!(syms[0], syms[2]);
+        assert!(store.intern_syms::<&str>(&[]).is_empty());
+    }
+
+    #[test]
+    fn wellknown_syms() {
+        let mut store = Store::<Fr>::default();
+
+        for (wellknown, name) in WellKnown::ALL.iter().zip(LURK_EXTERNAL_SYMBOL_NAMES) {
+            assert_eq!(*name, wellknown.name());
+            let ptr = store.lurk_sym(name);
+            assert_eq!(Some(*wellknown), store.is_wellknown_sym(&ptr));
+        }
+
+        let lambda = store.sym("lambda");
+        assert_eq!(Some(WellKnown::Lambda), store.is_wellknown_sym(&lambda));
+        let nil = store.nil();
+        assert_eq!(Some(WellKnown::Nil), store.is_wellknown_sym(&nil));
+
+        let user_sym = store.sym("my-function");
+        assert_eq!(None, store.is_wellknown_sym(&user_sym));
+        let key = store.key("lambda");
+        assert_eq!(None, store.is_wellknown_sym(&key));
+        let num = store.num(store.wellknown_syms[0] as u64);
+        assert_eq!(None, store.is_wellknown_sym(&num));
+    }
+
+    #[test]
+    fn with_wellknown_syms() {
+        let names = ["defmacro", "quasiquote", "unquote"];
+        let mut a = Store::<Fr>::with_wellknown_syms(&names);
+        let mut b = Store::<Fr>::with_wellknown_syms(&names);
+
+        let user_sym = a.sym("my-function");
+        b.read("(my-function 1 2)").unwrap();
+        for name in names {
+            let ptr = a.sym(name);
+            assert_eq!(ptr, b.sym(name));
+            assert!(ptr.1.idx() < user_sym.1.idx());
+            assert_eq!(None, a.is_wellknown_sym(&ptr));
+        }
+
+        // The Lurk symbols keep their usual pointers.
+        let mut default = Store::<Fr>::default();
+        assert_eq!(default.sym("lambda"), a.sym("lambda"));
+        assert_eq!(default.nil(), a.nil());
+        assert_eq!(a.nil(), a.get_nil());
+    }
+
+    #[test]
+    fn try_merge() {
+        let mut a = Store::<Fr>::with_wellknown_syms(&["defmacro", "quasiquote"]);
+        let mut b = Store::<Fr>::with_wellknown_syms(&["defmacro", "quasiquote", "unquote"]);
+        a.read("(defmacro f (x) (quasiquote x))").unwrap();
+        let b_expr = b.read("(unquote \"b\" (1 . 2))").unwrap();
+        let outermost = b.intern_cont_outermost();
+        let b_cont = b
+            .try_intern_cont(Continuation::Lookup {
+                saved_env: b_expr,
+                continuation: outermost,
+            })
+            .unwrap();
+        a.hydrate_scalar_cache();
+        b.hydrate_scalar_cache();
+
+        // A vocabulary extending the other's is compatible.
+        let remap = a.try_merge(&b).unwrap();
+        let merged = remap.expr(&b_expr).unwrap();
+        assert_eq!(b.hash_expr(&b_expr), a.hash_expr(&merged));
+        let merged_cont = remap.cont(&b_cont).unwrap();
+        assert_eq!(b.hash_cont(&b_cont), a.hash_cont(&merged_cont));
+        // The stores agree on the symbols they share, so these map to themselves.
+        let defmacro = b.sym("defmacro");
+        assert_eq!(Some(defmacro), remap.expr(&defmacro));
+
+        let mut c = Store::<Fr>::with_wellknown_syms(&["defmacro", "backquote"]);
+        let backquote = c.sym("backquote");
+        let quasiquote = a.sym("quasiquote");
+        let conflict = MergeConflict {
+            position: 1,
+            ours: c.sym_name_cow(&backquote).unwrap().into_owned(),
+            theirs: a.sym_name_cow(&quasiquote).unwrap().into_owned(),
+        };
+        let sizes = c.substore_sizes();
+        assert_eq!(Err(conflict), c.try_merge(&a));
+        assert_eq!(sizes, c.substore_sizes());
+
+        // Stores without a vocabulary of their own merge with anything.
+        let mut d = Store::<Fr>::default();
+        assert!(d.try_merge(&c).is_ok());
+    }
+
+    #[test]
+    fn tag_from_scalar() {
+        let mut store = Store::<Fr>::default();
+        let cons = store.read("(1 . 2)").unwrap();
+        let scalar = store.hash_expr(&cons).unwrap();
+
+        assert_eq!(Some(ExprTag::Cons), store.tag_from_scalar(&scalar));
+        assert_eq!(Some(cons), store.fetch_scalar(&scalar));
+
+        let sym = store.sym("x");
+        let scalar = store.hash_expr(&sym).unwrap();
+        assert_eq!(Some(ExprTag::Sym), store.tag_from_scalar(&scalar));
+    }
+
+    #[test]
+    fn constants_cache_shared_across_stores() {
+        let s1 = Store::<Fr>::default();
+        let s2 = Store::<Fr>::default();
+        assert!(Arc::ptr_eq(
+            &s1.poseidon_cache.constants,
+            &s2.poseidon_cache.constants
+        ));
+
+        for s in [&s1, &s2] {
+            s.poseidon_constants().c3();
+            s.poseidon_constants().c4();
+            s.poseidon_constants().c6();
+            s.poseidon_constants().c8();
+        }
+        // One build per arity, no matter how many stores (or other tests) use them.
+        assert_eq!(4, s1.poseidon_constants().build_count());
+    }
+
+    #[test]
+    fn validate_store() {
+        let mut store = Store::<Fr>::default();
+
+        let list = store.read("(1 (2 . \"three\") four)").unwrap();
+        let env = empty_sym_env(&store);
+        let arg = store.sym("x");
+        store.intern_fun(arg, list, env);
+        assert_eq!(Ok(()), store.validate());
+
+        // Corrupt the store by hand: a cons whose cdr points past the end of `cons_store`.
+        let one = store.num(1);
+        let dangling = Ptr(ExprTag::Cons, RawPtr::new(9999));
+        let (index, _) = store.cons_store.insert_full((one, dangling));
+
+        assert_eq!(
+            Err(StoreError::DanglingPtr {
+                store: "cons_store",
+                index,
+                tag: "cons#".into(),
+                ptr_index: 9999,
+            }),
+            store.validate()
+        );
+    }
+
     #[test]
     fn opaque_comm_fmt() {
         let s = &mut Store::<Fr>::default();
@@ -8,6 +8,9 @@ use crate::field::LurkField;
 use crate::store::TypePredicates;
 
 pub trait Tag: Into<u16> + TryFrom<u16> + Copy + Sized + Eq + fmt::Debug {
+    /// What the `Debug` output of an `SPtr` tagged by this type calls it.
+    const SCALAR_PTR_NAME: &'static str = "SPtr";
+
     fn from_field<F: LurkField>(f: &F) -> Option<Self>;
     fn to_field<F: LurkField>(&self) -> F;
 
@@ -69,6 +72,25 @@ impl TryFrom<u16> for ExprTag {
     }
 }
 
+impl ExprTag {
+    /// The variant name, e.g. `"Cons"`. `Display` instead uses the lowercase, `#`-suffixed form.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            ExprTag::Nil => "Nil",
+            ExprTag::Cons => "Cons",
+            ExprTag::Sym => "Sym",
+            ExprTag::Fun => "Fun",
+            ExprTag::Num => "Num",
+            ExprTag::Thunk => "Thunk",
+            ExprTag::Str => "Str",
+            ExprTag::Char => "Char",
+            ExprTag::Comm => "Comm",
+            ExprTag::U64 => "U64",
+            ExprTag::Key => "Key",
+        }
+    }
+}
+
 impl fmt::Display for ExprTag {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -113,6 +135,8 @@ impl TypePredicates for ExprTag {
 }
 
 impl Tag for ExprTag {
+    const SCALAR_PTR_NAME: &'static str = "ScalarPtr";
+
     fn from_field<F: LurkField>(f: &F) -> Option<Self> {
         Self::try_from(f.to_u16()?).ok()
     }
@@ -190,6 +214,8 @@ impl TryFrom<u16> for ContTag {
 }
 
 impl Tag for ContTag {
+    const SCALAR_PTR_NAME: &'static str = "ScalarContPtr";
+
     fn from_field<F: LurkField>(f: &F) -> Option<Self> {
         Self::try_from(f.to_u16()?).ok()
     }
@@ -206,6 +232,30 @@ impl Tag for ContTag {
     }
 }
 
+impl ContTag {
+    /// The variant name, e.g. `"Call0"`. `Display` instead uses the lowercase, `#`-suffixed form.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            ContTag::Outermost => "Outermost",
+            ContTag::Call0 => "Call0",
+            ContTag::Call => "Call",
+            ContTag::Call2 => "Call2",
+            ContTag::Tail => "Tail",
+            ContTag::Error => "Error",
+            ContTag::Lookup => "Lookup",
+            ContTag::Unop => "Unop",
+            ContTag::Binop => "Binop",
+            ContTag::Binop2 => "Binop2",
+            ContTag::If => "If",
+            ContTag::Let => "Let",
+            ContTag::LetRec => "LetRec",
+            ContTag::Dummy => "Dummy",
+            ContTag::Terminal => "Terminal",
+            ContTag::Emit => "Emit",
+        }
+    }
+}
+
 impl fmt::Display for ContTag {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -535,6 +585,39 @@ impl fmt::Display for Op2 {
     }
 }
 
+/// Any tag. `ExprTag`, `ContTag`, `Op1` and `Op2` share one 16-bit namespace, distinguished by the top nibble:
+/// `0b0000` for expressions, `0b0001` for continuations, `0b0010` for unary and `0b0011` for binary operators.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AnyTag {
+    Expr(ExprTag),
+    Cont(ContTag),
+    Op1(Op1),
+    Op2(Op2),
+}
+
+impl AnyTag {
+    /// Decode `f` into whichever kind of tag its top nibble selects.
+    pub fn from_field<F: LurkField>(f: &F) -> Option<Self> {
+        let u = f.to_u16()?;
+        match u >> 12 {
+            0b0000 => ExprTag::try_from(u).ok().map(Self::Expr),
+            0b0001 => ContTag::try_from(u).ok().map(Self::Cont),
+            0b0010 => Op1::try_from(u).ok().map(Self::Op1),
+            0b0011 => Op2::try_from(u).ok().map(Self::Op2),
+            _ => None,
+        }
+    }
+
+    pub fn to_field<F: LurkField>(&self) -> F {
+        match self {
+            Self::Expr(tag) => tag.to_field(),
+            Self::Cont(tag) => tag.to_field(),
+            Self::Op1(op) => op.to_field(),
+            Self::Op2(op) => op.to_field(),
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
 
@@ -550,6 +633,23 @@ pub mod tests {
     }
     }
 
+    proptest! {
+    #[test]
+    fn prop_tag_names(x in any::<ExprTag>(), y in any::<ContTag>()) {
+        assert_eq!(format!("{x:?}"), x.name());
+        assert_eq!(format!("{y:?}"), y.name());
+    }
+    }
+
+    #[test]
+    fn tag_names() {
+        assert_eq!("Cons", ExprTag::Cons.name());
+        assert_eq!("U64", ExprTag::U64.name());
+        assert_eq!("Binop2", ContTag::Binop2.name());
+        assert_eq!("LetRec", ContTag::LetRec.name());
+        assert_eq!("cons#", format!("{}", ExprTag::Cons));
+    }
+
     proptest! {
     #[test]
     fn prop_cont_tag_u16(x in any::<ContTag>()) {
@@ -576,4 +676,28 @@ pub mod tests {
         assert_eq!(x, x2)
     }
     }
+
+    #[test]
+    fn any_tag_from_field() {
+        use blstrs::Scalar as Fr;
+
+        let test = |tag: AnyTag| {
+            assert_eq!(Some(tag), AnyTag::from_field(&tag.to_field::<Fr>()));
+        };
+        test(AnyTag::Expr(ExprTag::Cons));
+        test(AnyTag::Cont(ContTag::Emit));
+        test(AnyTag::Op1(Op1::Car));
+        test(AnyTag::Op2(Op2::Eval));
+
+        // Unused values within a namespace, an unused namespace and values beyond 16 bits don't decode.
+        assert_eq!(
+            None,
+            AnyTag::from_field(&Fr::from(0b0000_0000_1111_1111u64))
+        );
+        assert_eq!(
+            None,
+            AnyTag::from_field(&Fr::from(0b0100_0000_0000_0000u64))
+        );
+        assert_eq!(None, AnyTag::from_field(&Fr::from(1u64 << 16)));
+    }
 }
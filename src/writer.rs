@@ -1,4 +1,6 @@
 use crate::field::LurkField;
+use crate::package::WellKnown;
+use crate::parser::CHAR_NAMES;
 use crate::store::{ContPtr, Continuation, Expression, Ptr, Store};
 use crate::Sym;
 use std::io;
@@ -97,7 +99,16 @@ impl<F: LurkField> Write<F> for Expression<'_, F> {
                 f.continuation.fmt(store, w)?;
                 write!(w, "}}")
             }
-            Cons(_, _) => {
+            Cons(car, cdr) => {
+                if store.abbreviate_quote() && store.is_wellknown_sym(car) == Some(WellKnown::Quote)
+                {
+                    if let Some(Cons(quoted, rest)) = store.fetch(cdr) {
+                        if let Some(Nil) = store.fetch(&rest) {
+                            write!(w, "'")?;
+                            return quoted.fmt(store, w);
+                        }
+                    }
+                }
                 write!(w, "(")?;
                 self.print_tail(store, w)
             }
@@ -110,9 +121,11 @@ impl<F: LurkField> Write<F> for Expression<'_, F> {
                 write!(w, ")")
             }
             Opaque(f) => f.fmt(store, w),
-            Char(c) => {
-                write!(w, "#\\{c}")
-            }
+            Char(c) => match CHAR_NAMES.iter().find(|(_, named)| named == c) {
+                Some((name, _)) => write!(w, "#\\{name}"),
+                None if c.is_control() => write!(w, "#\\u{{{:x}}}", u32::from(*c)),
+                None => write!(w, "#\\{c}"),
+            },
             UInt(n) => write!(w, "{n}u64"),
         }
     }